@@ -0,0 +1,78 @@
+//! Builds a small scene programmatically and renders it.
+//!
+//! ```bash
+//! cargo run --release --example builder
+//! ```
+
+use std::sync::Arc;
+
+use rtxon::{
+    hitable::{sphere::Sphere, xz_rectangle::XZRectangle},
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+    },
+    sampler::SamplerKind,
+    texture::checker_texture::CheckerTexture,
+    Camera, Color, Config, Renderer, Result, Scene, Vec3,
+};
+
+fn main() -> Result<()> {
+    let aspect_ratio = 16.0 / 9.0;
+    let look_from = Vec3::new(0.0, 1.0, 3.0);
+    let look_at = Vec3::new(0.0, 0.0, -1.0);
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.05,
+        (look_from - look_at).length(),
+        None,
+    );
+
+    let checker = Arc::new(CheckerTexture::new(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+
+    let scene = Scene::builder()
+        .camera(camera)
+        .aspect_ratio(aspect_ratio)
+        .background(Color::new(0.05, 0.05, 0.1))
+        .add(Sphere::new(
+            Vec3::new(0.0, -100.5, -1.0),
+            100.0,
+            Arc::new(Lambertian::new_from_texture(checker)),
+        ))
+        .add(Sphere::new(
+            Vec3::new(-1.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Dielectric::new(1.5)),
+        ))
+        .add(Sphere::new(
+            Vec3::new(0.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.3))),
+        ))
+        .add(Sphere::new(
+            Vec3::new(1.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.05)),
+        ))
+        .add(XZRectangle::new(
+            (-1.5, 1.5),
+            (-2.0, 0.0),
+            2.0,
+            Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))),
+        ))
+        .build()?;
+
+    let config = Config::new(scene.aspect_ratio, 400, 128, 50, SamplerKind::Sobol, 1);
+
+    let mut renderer = Renderer::new(config, scene.world, scene.camera, None);
+    renderer.render_current_frame_threadpool(scene.background, 4, 16)?;
+
+    renderer.save("builder.png")
+}
@@ -0,0 +1,17 @@
+//! Renders the built-in Cornell box scene.
+//!
+//! ```bash
+//! cargo run --release --example cornell_box
+//! ```
+
+use rtxon::{sampler::SamplerKind, scene, Config, Renderer, Result};
+
+fn main() -> Result<()> {
+    let scene = scene::cornell_box_scene();
+    let config = Config::new(scene.aspect_ratio, 300, 64, 50, SamplerKind::Sobol, 42);
+
+    let mut renderer = Renderer::new(config, scene.world, scene.camera, None);
+    renderer.render_current_frame_threadpool(scene.background, 4, 16)?;
+
+    renderer.save("cornell_box.png")
+}
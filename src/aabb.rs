@@ -13,28 +13,15 @@ pub struct Aabb {
 impl Hitable for Aabb {
     // We don't care about returning actual values in the hit record just that we hit SOME record
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        for a in 0..3 {
-            let t0 = f64::min(
-                self.minimum.at(a).unwrap() - r.origin.at(a).unwrap() / r.direction.at(a).unwrap(),
-                self.maximum.at(a).unwrap() - r.origin.at(a).unwrap() / r.direction.at(a).unwrap(),
-            );
-            let t1 = f64::max(
-                self.minimum.at(a).unwrap() - r.origin.at(a).unwrap() / r.direction.at(a).unwrap(),
-                self.maximum.at(a).unwrap() - r.origin.at(a).unwrap() / r.direction.at(a).unwrap(),
-            );
-
-            let t_min = f64::max(t0, t_min);
-            let t_max = f64::min(t1, t_max);
-
-            if t_max <= t_min {
-                return None;
-            }
+        if self.andrew_kensler_hit(r, t_min, t_max) {
+            Some(HitRecord::default())
+        } else {
+            None
         }
-        Some(HitRecord::default())
     }
 
-    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
-        todo!()
+    fn bounding_box(&self, _time: (f64, f64)) -> Option<Aabb> {
+        Some(*self)
     }
 }
 
@@ -43,18 +30,19 @@ impl Aabb {
         Aabb { minimum, maximum }
     }
 
-    pub fn andrew_kensler_hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+    pub fn andrew_kensler_hit(&self, r: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        // The interval shrinks with every slab, the ray misses once it is empty
         for a in 0..3 {
             let inv_d = 1.0 / r.direction.at(a).unwrap();
-            let mut t0 = self.minimum.at(a).unwrap() - r.origin.at(a).unwrap() * inv_d;
-            let mut t1 = self.maximum.at(a).unwrap() - r.origin.at(a).unwrap() * inv_d;
+            let mut t0 = (self.minimum.at(a).unwrap() - r.origin.at(a).unwrap()) * inv_d;
+            let mut t1 = (self.maximum.at(a).unwrap() - r.origin.at(a).unwrap()) * inv_d;
 
             if inv_d < 0.0 {
                 std::mem::swap(&mut t0, &mut t1);
             }
 
-            let t_min = if t0 > t_min { t0 } else { t_min };
-            let t_max = if t1 < t_max { t1 } else { t_max };
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
 
             if t_max <= t_min {
                 return false;
@@ -72,9 +60,9 @@ impl Aabb {
         );
 
         let big = Vec3::new(
-            f64::min(box0.maximum.x, box1.maximum.x),
-            f64::min(box0.maximum.y, box1.maximum.y),
-            f64::min(box0.maximum.z, box1.maximum.z),
+            f64::max(box0.maximum.x, box1.maximum.x),
+            f64::max(box0.maximum.y, box1.maximum.y),
+            f64::max(box0.maximum.z, box1.maximum.z),
         );
 
         Aabb {
@@ -83,3 +71,25 @@ impl Aabb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Aabb;
+    use crate::{ray::Ray, vec3::Vec3};
+
+    #[test]
+    fn should_hit_box_in_front_only() {
+        let b = Aabb::new(Vec3::new(2.0, -1.0, -1.0), Vec3::new(4.0, 1.0, 1.0));
+        let towards = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let away = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let above = Ray::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(1.0, 0.1, 0.0));
+        // Inside every slab, but not at the same time
+        let diagonal = Ray::new(Vec3::new(0.0, -6.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+
+        assert!(b.andrew_kensler_hit(&towards, 0.001, f64::INFINITY));
+        assert!(!b.andrew_kensler_hit(&towards, 0.001, 1.5));
+        assert!(!b.andrew_kensler_hit(&away, 0.001, f64::INFINITY));
+        assert!(!b.andrew_kensler_hit(&above, 0.001, f64::INFINITY));
+        assert!(!b.andrew_kensler_hit(&diagonal, 0.001, f64::INFINITY));
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{camera::Camera, utils::degrees_to_radians, vec3::Vec3};
+
+/// Values that can be interpolated between keyframes.
+pub trait Keyable: Copy {
+    fn add(self, other: Self) -> Self;
+    fn scale(self, factor: f64) -> Self;
+}
+
+impl Keyable for f64 {
+    fn add(self, other: f64) -> f64 {
+        self + other
+    }
+
+    fn scale(self, factor: f64) -> f64 {
+        self * factor
+    }
+}
+
+impl Keyable for Vec3 {
+    fn add(self, other: Vec3) -> Vec3 {
+        self + other
+    }
+
+    fn scale(self, factor: f64) -> Vec3 {
+        self * factor
+    }
+}
+
+/// How a track moves from a keyframe to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Straight line, constant speed.
+    Linear,
+    /// Cubic Bézier curve with handles following the neighbouring keyframes, so the
+    /// motion is smooth through the keyframes.
+    Bezier,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe<T> {
+    pub time: f64,
+    pub value: T,
+    pub interpolation: Interpolation,
+}
+
+/// Value changing over time, held constant before the first and after the last keyframe.
+#[derive(Debug, Clone)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T: Keyable> Track<T> {
+    /// Track with a single keyframe at `time`.
+    pub fn new(time: f64, value: T, interpolation: Interpolation) -> Self {
+        Track {
+            keys: vec![Keyframe {
+                time,
+                value,
+                interpolation,
+            }],
+        }
+    }
+
+    /// Track that never changes.
+    pub fn constant(value: T) -> Self {
+        Self::new(0.0, value, Interpolation::Linear)
+    }
+
+    /// Adds a keyframe, replacing any keyframe at the same time.
+    pub fn key(mut self, time: f64, value: T, interpolation: Interpolation) -> Self {
+        let keyframe = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+        match self.keys.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(index) => self.keys[index] = keyframe,
+            Err(index) => self.keys.insert(index, keyframe),
+        }
+        self
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.keys.len() == 1
+    }
+
+    /// Value of the track at `time`.
+    pub fn sample(&self, time: f64) -> T {
+        let next = self.keys.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return self.keys[0].value;
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].value;
+        }
+
+        let k1 = &self.keys[next - 1];
+        let k2 = &self.keys[next];
+        let u = (time - k1.time) / (k2.time - k1.time);
+
+        match k1.interpolation {
+            Interpolation::Linear => k1.value.scale(1.0 - u).add(k2.value.scale(u)),
+            Interpolation::Bezier => {
+                // Catmull-Rom tangents for uneven keyframe spacing, flat at the first and
+                // last keyframes so the motion eases in and out
+                let span = k2.time - k1.time;
+                let handle = |before: &Keyframe<T>, after: &Keyframe<T>| {
+                    after
+                        .value
+                        .add(before.value.scale(-1.0))
+                        .scale(span / (3.0 * (after.time - before.time)))
+                };
+                let c1 = match next {
+                    1 => k1.value,
+                    _ => k1.value.add(handle(&self.keys[next - 2], k2)),
+                };
+                let c2 = match self.keys.get(next + 1) {
+                    None => k2.value,
+                    Some(k3) => k2.value.add(handle(k1, k3).scale(-1.0)),
+                };
+
+                let v = 1.0 - u;
+                k1.value
+                    .scale(v * v * v)
+                    .add(c1.scale(3.0 * v * v * u))
+                    .add(c2.scale(3.0 * v * u * u))
+                    .add(k2.value.scale(u * u * u))
+            }
+        }
+    }
+}
+
+/// Rotation, uniform scale and translation, applied in that order.
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    rows: [Vec3; 3],
+    scale: f64,
+    translation: Vec3,
+}
+
+impl Transform {
+    /// `rotation` holds Euler angles in degrees, applied around x, then y, then z.
+    pub fn new(translation: Vec3, rotation: Vec3, scale: f64) -> Self {
+        let (sx, cx) = degrees_to_radians(rotation.x).sin_cos();
+        let (sy, cy) = degrees_to_radians(rotation.y).sin_cos();
+        let (sz, cz) = degrees_to_radians(rotation.z).sin_cos();
+
+        // Rz * Ry * Rx
+        let rows = [
+            Vec3::new(cz * cy, cz * sy * sx - sz * cx, cz * sy * cx + sz * sx),
+            Vec3::new(sz * cy, sz * sy * sx + cz * cx, sz * sy * cx - cz * sx),
+            Vec3::new(-sy, cy * sx, cy * cx),
+        ];
+
+        Transform {
+            rows,
+            scale,
+            translation,
+        }
+    }
+
+    fn rotate(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            Vec3::dot(&self.rows[0], &v),
+            Vec3::dot(&self.rows[1], &v),
+            Vec3::dot(&self.rows[2], &v),
+        )
+    }
+
+    fn rotate_inverse(&self, v: Vec3) -> Vec3 {
+        self.rows[0] * v.x + self.rows[1] * v.y + self.rows[2] * v.z
+    }
+
+    pub fn point(&self, p: Vec3) -> Vec3 {
+        self.rotate(p) * self.scale + self.translation
+    }
+
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        self.rotate(v) * self.scale
+    }
+
+    /// Normals only rotate, the scale is uniform.
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        self.rotate(n)
+    }
+
+    pub fn inverse_point(&self, p: Vec3) -> Vec3 {
+        self.rotate_inverse(p - self.translation) / self.scale
+    }
+
+    pub fn inverse_vector(&self, v: Vec3) -> Vec3 {
+        self.rotate_inverse(v) / self.scale
+    }
+}
+
+/// Keyframed translation, rotation (Euler angles in degrees) and uniform scale.
+#[derive(Debug, Clone)]
+pub struct TransformTrack {
+    pub translation: Track<Vec3>,
+    pub rotation: Track<Vec3>,
+    pub scale: Track<f64>,
+}
+
+impl TransformTrack {
+    pub fn new(translation: Track<Vec3>, rotation: Track<Vec3>, scale: Track<f64>) -> Self {
+        TransformTrack {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn sample(&self, time: f64) -> Transform {
+        Transform::new(
+            self.translation.sample(time),
+            self.rotation.sample(time),
+            self.scale.sample(time),
+        )
+    }
+
+    /// Times of all keyframes, sorted.
+    pub fn key_times(&self) -> Vec<f64> {
+        let mut times: Vec<f64> = self
+            .translation
+            .keys()
+            .iter()
+            .map(|k| k.time)
+            .chain(self.rotation.keys().iter().map(|k| k.time))
+            .chain(self.scale.keys().iter().map(|k| k.time))
+            .collect();
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+        times
+    }
+}
+
+/// Keyframed camera, the focus distance follows the distance to `look_at`.
+#[derive(Debug, Clone)]
+pub struct CameraAnimation {
+    pub look_from: Track<Vec3>,
+    pub look_at: Track<Vec3>,
+    pub vfov: Track<f64>,
+    pub aperture: Track<f64>,
+    pub vup: Vec3,
+}
+
+impl CameraAnimation {
+    pub fn new(
+        look_from: Track<Vec3>,
+        look_at: Track<Vec3>,
+        vfov: Track<f64>,
+        aperture: Track<f64>,
+    ) -> Self {
+        CameraAnimation {
+            look_from,
+            look_at,
+            vfov,
+            aperture,
+            vup: Vec3::new(0.0, 1.0, 0.0),
+        }
+    }
+
+    /// Camera placed as it is at `time`, with `shutter` as its shutter interval.
+    pub fn camera(&self, time: f64, aspect_ratio: f64, shutter: Option<(f64, f64)>) -> Camera {
+        let look_from = self.look_from.sample(time);
+        let look_at = self.look_at.sample(time);
+        Camera::new(
+            look_from,
+            look_at,
+            self.vup,
+            self.vfov.sample(time),
+            aspect_ratio,
+            self.aperture.sample(time),
+            (look_from - look_at).length(),
+            shutter,
+        )
+    }
+}
+
+/// Shutter interval of frame number `frame` at `fps` frames per second, `open` and
+/// `close` are fractions of the frame duration from the start of the frame. Opening at 0
+/// and closing at 0.5 gives a 180° shutter, -0.25 and 0.25 centres it on the frame.
+pub fn frame_shutter(frame: usize, fps: f64, open: f64, close: f64) -> (f64, f64) {
+    let start = frame as f64 / fps;
+    (start + open / fps, start + close / fps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frame_shutter, Interpolation, Track, Transform};
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn should_interpolate_linearly() {
+        let track =
+            Track::new(0.0, 0.0, Interpolation::Linear).key(2.0, 4.0, Interpolation::Linear);
+        assert_eq!(track.sample(-1.0), 0.0);
+        assert_eq!(track.sample(0.5), 1.0);
+        assert_eq!(track.sample(3.0), 4.0);
+    }
+
+    #[test]
+    fn should_pass_through_bezier_keyframes() {
+        let track = Track::new(0.0, 0.0, Interpolation::Bezier)
+            .key(1.0, 1.0, Interpolation::Bezier)
+            .key(3.0, 3.0, Interpolation::Bezier);
+        assert!((track.sample(1.0) - 1.0).abs() < 1e-12);
+        // Eases in instead of moving at constant speed
+        assert!(track.sample(0.1) < 0.1);
+        // No kink at the middle keyframe
+        let h = 1e-6;
+        let before = (track.sample(1.0) - track.sample(1.0 - h)) / h;
+        let after = (track.sample(1.0 + h) - track.sample(1.0)) / h;
+        assert!((before - after).abs() < 1e-4);
+    }
+
+    #[test]
+    fn should_invert_transform() {
+        let transform = Transform::new(Vec3::new(1.0, -2.0, 3.0), Vec3::new(30.0, 45.0, 60.0), 2.0);
+        let p = Vec3::new(0.3, 0.7, -1.1);
+        assert!((transform.inverse_point(transform.point(p)) - p).length() < 1e-12);
+        let v = Vec3::new(0.0, 0.0, 1.0);
+        assert!((transform.vector(v).length() - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn should_open_shutter_at_frame_start() {
+        assert_eq!(frame_shutter(12, 24.0, 0.0, 0.5), (0.5, 0.5 + 0.5 / 24.0));
+        assert_eq!(frame_shutter(0, 2.0, -0.5, 0.5), (-0.25, 0.25));
+    }
+}
@@ -0,0 +1,335 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    camera::Camera,
+    compare::luminance,
+    error::Result,
+    hitable::HitRecord,
+    renderer::Renderer,
+    utils::{color::Color, get_corrected_color, is_hdr, suffixed_path},
+    vec3::Vec3,
+};
+
+/// Auxiliary image collected at the first hit of the camera rays.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    /// Colour the surface filters light by
+    Albedo,
+    /// Shading normal in world space
+    Normal,
+    /// Distance along the camera view axis
+    Depth,
+    /// World space position
+    Position,
+    /// Texture coordinates
+    Uv,
+    /// Material, numbered by first appearance in the image
+    MaterialId,
+    /// Top level object of the world, numbered in the order the scene adds them
+    ObjectId,
+    /// Light emitted by the first hit, or the background when nothing is hit
+    Emission,
+    /// Light reaching the first hit straight from an emitter or the background
+    Direct,
+    /// Light reaching the first hit after more bounces
+    Indirect,
+}
+
+impl Aov {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::Position => "position",
+            Aov::Uv => "uv",
+            Aov::MaterialId => "material_id",
+            Aov::ObjectId => "object_id",
+            Aov::Emission => "emission",
+            Aov::Direct => "direct",
+            Aov::Indirect => "indirect",
+        }
+    }
+}
+
+/// How the AOVs are saved.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AovLayout {
+    /// One image per AOV, in the format of the output, the AOV name is appended to the
+    /// file name.
+    Files,
+    /// One multi-layer EXR next to the output, with the linear beauty as first layer.
+    Exr,
+}
+
+/// What the first hit of one camera sample saw. The beauty is the sum of the emission,
+/// direct and indirect light.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AovSample {
+    pub beauty: Color,
+    pub albedo: Color,
+    pub normal: Vec3,
+    pub depth: f64,
+    pub position: Vec3,
+    pub uv: (f64, f64),
+    /// Address of the material, only meaningful within one render.
+    pub material_key: usize,
+    pub object_id: usize,
+    pub emission: Color,
+    pub direct: Color,
+    pub indirect: Color,
+    /// Variance of the mean luminance of the beauty once averaged, the sum of the squared
+    /// luminances of the samples before.
+    pub variance: f64,
+}
+
+impl AovSample {
+    /// Records the surface the camera ray first hit, seen from `cam`.
+    pub fn record_hit(&mut self, rec: &HitRecord, cam: &Camera) {
+        let (_, _, w) = cam.basis();
+        self.normal = rec.normal;
+        self.position = rec.p;
+        self.depth = Vec3::dot(&(rec.p - cam.origin()), &-w);
+        self.uv = (rec.u, rec.v);
+        self.material_key = std::sync::Arc::as_ptr(&rec.mat_ptr) as *const () as usize;
+        self.object_id = rec.object_id;
+    }
+
+    /// Sums the samples of a pixel, the IDs come from the first sample.
+    pub fn accumulate(&mut self, other: &AovSample) {
+        self.beauty = self.beauty + other.beauty;
+        self.albedo = self.albedo + other.albedo;
+        self.normal = self.normal + other.normal;
+        self.depth += other.depth;
+        self.position = self.position + other.position;
+        self.uv = (self.uv.0 + other.uv.0, self.uv.1 + other.uv.1);
+        self.emission = self.emission + other.emission;
+        self.direct = self.direct + other.direct;
+        self.indirect = self.indirect + other.indirect;
+        self.variance += other.variance;
+    }
+
+    /// Average of `samples` accumulated samples.
+    pub fn average(&self, samples: f64) -> AovSample {
+        let scale = 1.0 / samples;
+        let mean = luminance(&(self.beauty * scale));
+        AovSample {
+            beauty: self.beauty * scale,
+            albedo: self.albedo * scale,
+            normal: self.normal * scale,
+            depth: self.depth * scale,
+            position: self.position * scale,
+            uv: (self.uv.0 * scale, self.uv.1 * scale),
+            emission: self.emission * scale,
+            direct: self.direct * scale,
+            indirect: self.indirect * scale,
+            variance: f64::max(self.variance * scale - mean * mean, 0.0) * scale,
+            ..*self
+        }
+    }
+}
+
+/// Per pixel AOVs of a frame, rows are stored bottom first like `Renderer::frame`.
+#[derive(Clone)]
+pub struct AovBuffers {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<AovSample>,
+    // Material IDs numbered from 1 in reading order, 0 where nothing is hit
+    material_ids: Vec<usize>,
+}
+
+impl AovBuffers {
+    pub fn new(width: usize, height: usize, pixels: Vec<AovSample>) -> Self {
+        // Material addresses change from run to run, number them by first appearance
+        // from the top left corner instead
+        let mut numbers = HashMap::new();
+        let mut material_ids = vec![0; pixels.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let index = (height - 1 - y) * width + x;
+                let key = pixels[index].material_key;
+                if key != 0 {
+                    let next = numbers.len() + 1;
+                    material_ids[index] = *numbers.entry(key).or_insert(next);
+                }
+            }
+        }
+
+        AovBuffers {
+            width,
+            height,
+            pixels,
+            material_ids,
+        }
+    }
+
+    /// Linear values of an AOV, IDs are repeated in the three channels.
+    pub fn layer(&self, aov: Aov) -> Vec<Color> {
+        let id = |id: usize| Color::new(id as f64, id as f64, id as f64);
+        self.pixels
+            .iter()
+            .zip(&self.material_ids)
+            .map(|(p, material_id)| match aov {
+                Aov::Albedo => p.albedo,
+                Aov::Normal => Color::from(p.normal),
+                Aov::Depth => Color::new(p.depth, p.depth, p.depth),
+                Aov::Position => Color::from(p.position),
+                Aov::Uv => Color::new(p.uv.0, p.uv.1, 0.0),
+                Aov::MaterialId => id(*material_id),
+                Aov::ObjectId => id(p.object_id),
+                Aov::Emission => p.emission,
+                Aov::Direct => p.direct,
+                Aov::Indirect => p.indirect,
+            })
+            .collect()
+    }
+
+    /// AOV mapped to 8 bit colours for viewing: light is gamma corrected like the beauty,
+    /// normals are mapped from [-1, 1], depth and positions are normalised over the frame
+    /// and IDs get distinct colours.
+    pub fn display(&self, aov: Aov) -> Vec<Color> {
+        let layer = self.layer(aov);
+        let to_8bit =
+            |c: Color| get_corrected_color(Color::new(c.r * c.r, c.g * c.g, c.b * c.b), 1.0);
+        match aov {
+            Aov::Albedo | Aov::Emission | Aov::Direct | Aov::Indirect => layer
+                .into_iter()
+                .map(|c| get_corrected_color(c, 1.0))
+                .collect(),
+            Aov::Normal => layer
+                .into_iter()
+                .map(|c| to_8bit(c * 0.5 + Color::new(0.5, 0.5, 0.5)))
+                .collect(),
+            Aov::Uv => layer.into_iter().map(to_8bit).collect(),
+            Aov::Depth | Aov::Position => {
+                let values = || layer.iter().flat_map(|c| [c.r, c.g, c.b]);
+                let min = values().fold(f64::INFINITY, f64::min);
+                let max = values().fold(f64::NEG_INFINITY, f64::max);
+                let range = if max > min { max - min } else { 1.0 };
+                let normalise = |x: f64| (x - min) / range;
+                layer
+                    .into_iter()
+                    .map(|c| to_8bit(Color::new(normalise(c.r), normalise(c.g), normalise(c.b))))
+                    .collect()
+            }
+            Aov::MaterialId | Aov::ObjectId => layer
+                .into_iter()
+                .map(|c| to_8bit(id_color(c.r as usize)))
+                .collect(),
+        }
+    }
+
+    /// Saves one image per AOV next to `file_path`, `output_albedo.png` and so on. HDR
+    /// formats (EXR, Radiance HDR) keep the linear values.
+    pub fn save_files(&self, aovs: &[Aov], file_path: &str) -> Result<()> {
+        for aov in aovs {
+            let path = suffixed_path(file_path, aov.name());
+            if is_hdr(&path) {
+                let mut image = image::Rgb32FImage::new(self.width as u32, self.height as u32);
+                let layer = self.layer(*aov);
+                for (x, y, pixel) in image.enumerate_pixels_mut() {
+                    let c = layer[self.index(x as usize, y as usize)];
+                    *pixel = image::Rgb([c.r as f32, c.g as f32, c.b as f32]);
+                }
+                image.save(&path)?;
+            } else {
+                Renderer::save_image(&self.display(*aov), self.width, self.height, &path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the linear beauty and `aovs` as the layers of one EXR file.
+    pub fn save_layers(&self, aovs: &[Aov], file_path: &str) -> Result<()> {
+        use exr::prelude::{
+            Encoding, Image, ImageAttributes, IntegerBounds, Layer, LayerAttributes,
+            SpecificChannels, Vec2, WritableImage,
+        };
+
+        let size = Vec2(self.width, self.height);
+        let layer = |name: &str, pixels: Vec<Color>| {
+            Layer::new(
+                size,
+                LayerAttributes::named(name),
+                Encoding::FAST_LOSSLESS,
+                SpecificChannels::rgb(move |position: Vec2<usize>| {
+                    let c = pixels[self.index(position.x(), position.y())];
+                    (c.r as f32, c.g as f32, c.b as f32)
+                }),
+            )
+        };
+
+        let beauty = self.pixels.iter().map(|p| p.beauty).collect();
+        let layers: Vec<_> = std::iter::once(layer("beauty", beauty))
+            .chain(aovs.iter().map(|aov| layer(aov.name(), self.layer(*aov))))
+            .collect();
+
+        let image = Image::from_layers(
+            ImageAttributes::new(IntegerBounds::from_dimensions(size)),
+            layers,
+        );
+        image.write().to_file(file_path)?;
+        Ok(())
+    }
+
+    /// Saves `aovs` with `layout`, multi-layer files get the name of the output with an
+    /// `_aovs.exr` ending.
+    pub fn save(&self, aovs: &[Aov], layout: AovLayout, file_path: &str) -> Result<()> {
+        match layout {
+            AovLayout::Files => self.save_files(aovs, file_path),
+            AovLayout::Exr => {
+                let path = Path::new(&suffixed_path(file_path, "aovs")).with_extension("exr");
+                self.save_layers(aovs, &path.to_string_lossy())
+            }
+        }
+    }
+
+    // Index of the pixel at (x, y) from the top left corner
+    fn index(&self, x: usize, y: usize) -> usize {
+        (self.height - 1 - y) * self.width + x
+    }
+}
+
+// Well spread colour for an ID, black for 0
+fn id_color(id: usize) -> Color {
+    if id == 0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    // Golden ratio steps around the hue circle
+    let hue = (id as f64 * 0.618_033_988_749_895).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    match hue as usize {
+        0 => Color::new(1.0, x, 0.0),
+        1 => Color::new(x, 1.0, 0.0),
+        2 => Color::new(0.0, 1.0, x),
+        3 => Color::new(0.0, x, 1.0),
+        4 => Color::new(x, 0.0, 1.0),
+        _ => Color::new(1.0, 0.0, x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{id_color, Aov, AovBuffers, AovSample};
+
+    #[test]
+    fn should_number_materials_in_reading_order() {
+        let sample = |material_key| AovSample {
+            material_key,
+            ..Default::default()
+        };
+        // Bottom row first: the top row holds materials 900 then 0 (a miss)
+        let buffers = AovBuffers::new(2, 2, vec![sample(300), sample(900), sample(900), sample(0)]);
+        let ids: Vec<f64> = buffers.layer(Aov::MaterialId).iter().map(|c| c.r).collect();
+        assert_eq!(ids, vec![2.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn should_give_neighbouring_ids_different_colors() {
+        for id in 1..20 {
+            assert_ne!(id_color(id), id_color(id + 1));
+        }
+        assert_eq!(id_color(0), Default::default());
+    }
+}
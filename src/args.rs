@@ -1,34 +1,449 @@
-use clap::Parser;
+use std::{ops::Range, path::Path};
+
+use clap::{Parser, Subcommand};
+
+use rtxon::{
+    animation,
+    aov::{Aov, AovLayout},
+    camera::{ProjectionKind, ShutterShape},
+    compare::Metric,
+    config::Region,
+    debug::RenderMode,
+    film::FilterKind,
+    integrator::IntegratorKind,
+    rig::Layout,
+    sampler::SamplerKind,
+    scene,
+    settings::{ConfigFile, Preset, RenderSettings, SceneFile, Settings},
+};
 
 /// A Raytracer In One Weekend implementation
+///
+/// Without a subcommand the options are those of `rtxon render`.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
-    /// Scene to render (default Cornell Box)
-    #[arg(short, long, default_value_t = 9)]
-    pub scene: usize,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Same as `rtxon list-scenes`
+    #[arg(long, hide = true)]
+    pub list_scenes: bool,
+
+    #[command(flatten)]
+    pub render: RenderArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RenderArgs {
+    /// Built-in scene to render, by name or index (see list-scenes), or a TOML scene file
+    /// with render settings of its own
+    #[arg(short, long, default_value = "cornell-box", value_parser = parse_scene)]
+    pub scene: SceneFile,
+
+    /// Samples, maximum depth and denoising for a quality level, the config file can pick
+    /// one too
+    #[arg(long, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// Config file with the default settings [default: rtxon.toml if there is one]
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// Image width in pixels [default: 600 or the width of the scene]
+    #[arg(short, long)]
+    pub width: Option<usize>,
+
+    /// Samples per pixel [default: 128 or the samples of the scene]
+    #[arg(short = 'n', long)]
+    pub samples: Option<i32>,
+
+    /// Maximum number of rays of a path, the camera ray included, so one more than its
+    /// bounces [default: 100]
+    #[arg(short, long)]
+    pub max_depth: Option<usize>,
 
-    /// Size of the tuple in decimal digits
-    #[arg(short, long, default_value_t = 600)]
-    pub width: usize,
+    /// Maximum number of diffuse bounces of a path [default: only --max-depth limits them]
+    #[arg(long, value_name = "DEPTH")]
+    pub diffuse_depth: Option<usize>,
 
-    /// Samples per pixel
-    #[arg(short, long, default_value_t = 128)]
-    pub samples: i32,
+    /// Maximum number of specular bounces of a path [default: only --max-depth limits them]
+    #[arg(long, value_name = "DEPTH")]
+    pub specular_depth: Option<usize>,
 
-    /// Primorial offset
-    #[arg(short, long, default_value_t = 100)]
-    pub max_depth: usize,
+    /// Maximum number of transmission bounces of a path [default: only --max-depth limits them]
+    #[arg(long, value_name = "DEPTH")]
+    pub transmission_depth: Option<usize>,
+
+    /// Bounces before Russian roulette may end paths carrying little light, one less than
+    /// --max-depth or more turns it off [default: 3]
+    #[arg(long, value_name = "DEPTH")]
+    pub roulette_depth: Option<usize>,
 
     /// Desired output location
     #[arg(short, long, default_value_t = String::from("output.png"))]
     pub output_path: String,
 
+    /// Threads [default: 1]
+    #[arg(short, long)]
+    pub threads: Option<usize>,
+
+    /// Threadpool Chunks [default: 1]
+    #[arg(short, long)]
+    pub chunks: Option<usize>,
+
+    /// Sample generator used for pixel, lens, time and bounce samples [default: independent]
+    #[arg(long, value_enum)]
+    pub sampler: Option<SamplerKind>,
+
+    /// Integrator computing the light of the camera rays
+    #[arg(long, value_enum, default_value_t = IntegratorKind::Path)]
+    pub integrator: IntegratorKind,
+
+    /// Reach of the ambient occlusion rays [default: a tenth of the size of the scene]
+    #[arg(long, value_name = "DISTANCE")]
+    pub ao_distance: Option<f64>,
+
+    /// Path trace the scene or show one of the debug views instead
+    #[arg(long, value_enum, default_value_t = RenderMode::Path, conflicts_with_all = ["aov", "denoise", "integrator", "ao_distance"])]
+    pub mode: RenderMode,
+
+    /// Reconstruction filter spreading every sample over the pixels around it
+    #[arg(long, value_enum, default_value_t = FilterKind::Box)]
+    pub filter: FilterKind,
+
+    /// Radius of the reconstruction filter in pixels (default depends on the filter)
+    #[arg(long)]
+    pub filter_radius: Option<f64>,
+
+    /// Seed for scene generation and sampling, the same seed always renders the same image
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Only render this window, in pixels from the top left corner or in fractions of the
+    /// image size (0.25,0.5,0.75,1.0)
+    #[arg(long, value_name = "X0,Y0,X1,Y1", value_parser = parse_region)]
+    pub region: Option<RegionArg>,
+
+    /// Save only the --region instead of a full-size image black outside of it
+    #[arg(long, requires = "region")]
+    pub crop: bool,
+
+    /// Also save these auxiliary images of the first hits (comma separated)
+    #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "rig")]
+    pub aov: Vec<Aov>,
+
+    /// Save the AOVs as separate images or as the layers of one EXR
+    #[arg(long, value_enum, default_value_t = AovLayout::Files)]
+    pub aov_layout: AovLayout,
+
+    /// Also save a denoised copy of the render, with "_denoised" appended to the file name
+    #[arg(long, conflicts_with = "rig", overrides_with = "no_denoise")]
+    pub denoise: bool,
+
+    /// Do not denoise even if the preset or the config file asks for it
+    #[arg(long, overrides_with = "denoise")]
+    pub no_denoise: bool,
+
+    /// Passes of the denoiser, each one doubles its reach
+    #[arg(long, default_value_t = 5)]
+    pub denoise_iterations: usize,
+
+    /// Trace the samples of this pixel, from the top left corner, instead of rendering and
+    /// print every bounce as JSON lines
+    #[arg(long, value_name = "X,Y", value_parser = parse_pixel, conflicts_with_all = ["rig", "frames", "aov", "denoise", "mode", "integrator"])]
+    pub trace_pixel: Option<(usize, usize)>,
+
+    /// Also write the render statistics printed at the end to this JSON file
+    #[arg(long, value_name = "FILE")]
+    pub stats_json: Option<String>,
+
+    /// Render an animation, frames START..END (or START..=END) are saved as numbered files
+    #[arg(long, value_name = "START..END", value_parser = parse_frames)]
+    pub frames: Option<Range<usize>>,
+
+    /// Frames per second of the animation
+    #[arg(long, default_value_t = 24.0)]
+    pub fps: f64,
+
+    /// When the shutter opens, as a fraction of the frame duration from the frame start,
+    /// stills are frame 0 [default: 0]
+    #[arg(long, allow_negative_numbers = true)]
+    pub shutter_open: Option<f64>,
+
+    /// When the shutter closes, 0.5 after opening at 0 is a 180 degree shutter [default: 0.5]
+    #[arg(long, allow_negative_numbers = true)]
+    pub shutter_close: Option<f64>,
+
+    /// How the shutter weights the times of the rays while open
+    #[arg(long, value_enum, default_value_t = ShutterShape::Box)]
+    pub shutter_shape: ShutterShape,
+
+    /// Override the projection of the scene camera, equirectangular renders at 2:1
+    #[arg(long, value_enum)]
+    pub projection: Option<ProjectionKind>,
+
+    /// Diameter of the image circle in degrees for fisheye projections
+    #[arg(long, default_value_t = 180.0)]
+    pub fisheye_fov: f64,
+
+    /// Move the camera along its view direction so the whole scene fits in the frame
+    #[arg(long)]
+    pub auto_frame: bool,
+
+    /// Focus on whatever the centre pixel sees
+    #[arg(long)]
+    pub autofocus: bool,
+
+    /// Focus on whatever this pixel sees, from the top left corner
+    #[arg(long, value_name = "X,Y", value_parser = parse_pixel)]
+    pub focus_pixel: Option<(usize, usize)>,
+
+    /// Render several views in one run instead of a single image
+    #[arg(long, value_enum)]
+    pub rig: Option<RigKind>,
+
+    /// Distance between the eyes of the stereo rig, in scene units
+    #[arg(long, default_value_t = 0.065)]
+    pub interocular: f64,
+
+    /// Distance at which the stereo eyes converge (default camera focus distance)
+    #[arg(long)]
+    pub convergence: Option<f64>,
+
+    /// How the views of a rig are saved
+    #[arg(long, value_enum, default_value_t = Layout::SideBySide)]
+    pub layout: Layout,
+
+    /// Number of aperture blades, polygonal out of focus highlights instead of disks
+    #[arg(long)]
+    pub aperture_blades: Option<usize>,
+
+    /// Rotation of the aperture blades in degrees
+    #[arg(long, default_value_t = 0.0)]
+    pub aperture_rotation: f64,
+
+    /// Grayscale image used as the aperture shape, white lets light through
+    #[arg(long, conflicts_with = "aperture_blades")]
+    pub aperture_mask: Option<String>,
+
+    /// Strength of the optical vignetting, giving cat-eye highlights toward the edges
+    #[arg(long)]
+    pub cat_eye: Option<f64>,
+}
+
+fn parse_frames(value: &str) -> Result<Range<usize>, String> {
+    let frame = |f: &str| f.trim().parse::<usize>().map_err(|e| e.to_string());
+    let range = if let Some((start, end)) = value.split_once("..=") {
+        frame(start)?..frame(end)? + 1
+    } else if let Some((start, end)) = value.split_once("..") {
+        frame(start)?..frame(end)?
+    } else {
+        let single = frame(value)?;
+        single..single + 1
+    };
+    if range.is_empty() {
+        return Err(String::from("the frame range is empty"));
+    }
+    Ok(range)
+}
+
+impl RenderArgs {
+    /// Shutter interval of frame number `frame`.
+    pub fn frame_shutter(&self, frame: usize) -> (f64, f64) {
+        animation::frame_shutter(
+            frame,
+            self.fps,
+            self.shutter_open.unwrap_or(0.0),
+            self.shutter_close.unwrap_or(0.5),
+        )
+    }
+
+    /// Defaults of the scene, overridden by the config file and its preset, the scene file,
+    /// the `--preset` and the rest of the command line in turn.
+    pub fn settings(&self) -> rtxon::Result<RenderSettings> {
+        let config = ConfigFile::find(self.config.as_deref())?;
+        let command_line = Settings {
+            width: self.width,
+            samples: self.samples,
+            max_depth: self.max_depth,
+            diffuse_depth: self.diffuse_depth,
+            specular_depth: self.specular_depth,
+            transmission_depth: self.transmission_depth,
+            roulette_depth: self.roulette_depth,
+            threads: self.threads,
+            chunks: self.chunks,
+            sampler: self.sampler,
+            denoise: match (self.denoise, self.no_denoise) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+        };
+
+        let mut settings = self
+            .scene
+            .settings_over(&config.defaults())
+            .overridden_by(&self.preset.map(|p| p.settings()).unwrap_or_default())
+            .overridden_by(&command_line)
+            .resolve();
+        // Presets and config files may ask for denoising where there is nothing to denoise
+        settings.denoise &=
+            self.mode == RenderMode::Path && self.rig.is_none() && self.trace_pixel.is_none();
+        Ok(settings)
+    }
+}
+
+// A scene file when the value names one, otherwise a built-in scene without settings
+fn parse_scene(value: &str) -> Result<SceneFile, String> {
+    if value.ends_with(".toml") || Path::new(value).is_file() {
+        return SceneFile::load(value).map_err(|e| e.to_string());
+    }
+    let scene = scene::find(value).map_err(|e| e.to_string())?;
+    Ok(SceneFile {
+        scene,
+        settings: Settings::default(),
+    })
+}
+
+fn parse_pixel(value: &str) -> Result<(usize, usize), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| String::from("expected X,Y"))?;
+    let coordinate = |c: &str| c.trim().parse::<usize>().map_err(|e| e.to_string());
+    Ok((coordinate(x)?, coordinate(y)?))
+}
+
+/// Render window given on the command line, fractions are resolved once the image size
+/// is known.
+#[derive(Debug, Clone, Copy)]
+pub enum RegionArg {
+    Pixels(Region),
+    Normalised([f64; 4]),
+}
+
+impl RegionArg {
+    pub fn region(&self, width: usize, height: usize) -> Region {
+        match *self {
+            RegionArg::Pixels(region) => region,
+            RegionArg::Normalised([x0, y0, x1, y1]) => {
+                Region::normalised(x0, y0, x1, y1, width, height)
+            }
+        }
+    }
+}
+
+// Values with a decimal point are fractions of the image size, integers are pixels
+fn parse_region(value: &str) -> Result<RegionArg, String> {
+    let values: Vec<&str> = value.split(',').map(str::trim).collect();
+    if values.len() != 4 {
+        return Err(String::from("expected X0,Y0,X1,Y1"));
+    }
+
+    if values.iter().any(|v| v.contains('.')) {
+        let mut fractions = [0.0; 4];
+        for (fraction, v) in fractions.iter_mut().zip(&values) {
+            *fraction = v.parse::<f64>().map_err(|e| e.to_string())?;
+        }
+        return Ok(RegionArg::Normalised(fractions));
+    }
+
+    let pixel = |v: &str| v.parse::<usize>().map_err(|e| e.to_string());
+    Ok(RegionArg::Pixels(Region::new(
+        pixel(values[0])?,
+        pixel(values[1])?,
+        pixel(values[2])?,
+        pixel(values[3])?,
+    )))
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RigKind {
+    /// Left and right eye views
+    Stereo,
+    /// Six 90 degree faces around the camera
+    CubeMap,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Render a scene, the default when no subcommand is given
+    Render(RenderArgs),
+    /// Show the settings of a render and what is in its scene
+    Info(RenderArgs),
+    /// List the built-in scenes with their default settings
+    ListScenes,
+    /// Convert an image to another format (PNG, EXR, HDR...), guessed from the extension
+    Convert(ConvertArgs),
+    /// Compare two images (PNG, EXR, HDR...) and report error metrics
+    Compare(CompareArgs),
+    /// Time the renderer on a fixed set of built-in scenes
+    Bench(BenchArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ConvertArgs {
+    /// Image to convert
+    pub input: String,
+
+    /// Converted image
+    pub output: String,
+
+    /// Exposure adjustment in stops, every stop doubles the brightness
+    #[arg(short, long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub exposure: f64,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CompareArgs {
+    /// Reference image
+    pub reference: String,
+
+    /// Image to evaluate against the reference
+    pub test: String,
+
+    /// Write a false-colour map of the per pixel error of --metric
+    #[arg(short, long)]
+    pub error_map: Option<String>,
+
+    /// Exit with a non-zero code when --metric is worse than this value
+    #[arg(short, long)]
+    pub threshold: Option<f64>,
+
+    /// Metric used by --threshold and --error-map
+    #[arg(short, long, value_enum, default_value_t = Metric::Flip)]
+    pub metric: Metric,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BenchArgs {
+    /// Timed renders of every scene
+    #[arg(short, long, default_value_t = 5)]
+    pub iterations: usize,
+
+    /// Untimed renders of every scene before the timed ones
+    #[arg(short, long, default_value_t = 1)]
+    pub warmup: usize,
+
     /// Threads
     #[arg(short, long, default_value_t = 1)]
     pub threads: usize,
 
     /// Threadpool Chunks
-    #[arg(short, long, default_value_t = 1)]
+    #[arg(short, long, default_value_t = 16)]
     pub chunks: usize,
+
+    /// Compare with the results saved by an earlier run and exit with a non-zero code
+    /// when a scene got slower than --tolerance
+    #[arg(short, long, value_name = "FILE")]
+    pub baseline: Option<String>,
+
+    /// Save the results as JSON, to be used as a --baseline later
+    #[arg(short, long, value_name = "FILE")]
+    pub save: Option<String>,
+
+    /// Drop of the median Mrays/s, in percent, above which a scene counts as a regression
+    #[arg(long, default_value_t = 5.0)]
+    pub tolerance: f64,
 }
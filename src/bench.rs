@@ -0,0 +1,244 @@
+//! Fixed render workloads timing the renderer the same way from run to run and machine to
+//! machine, and baselines to compare the timings with.
+
+use serde_json::{json, Value};
+
+use crate::{
+    error::{Error, Result},
+    sampler::SamplerKind,
+    scene, utils, Config, Renderer,
+};
+
+/// Seed of the scenes and samples of every workload.
+pub const SEED: u64 = 42;
+
+/// Built-in scene rendered with fixed settings, named after the scene.
+#[derive(Debug, Clone, Copy)]
+pub struct Workload {
+    pub name: &'static str,
+    pub width: usize,
+    pub samples: i32,
+    pub max_depth: usize,
+}
+
+/// Spheres, textures, emitters and a mesh, each kept to about a second per render.
+pub const WORKLOADS: [Workload; 4] = [
+    Workload {
+        name: "random-spheres",
+        width: 320,
+        samples: 4,
+        max_depth: 50,
+    },
+    Workload {
+        name: "checker",
+        width: 320,
+        samples: 16,
+        max_depth: 50,
+    },
+    Workload {
+        name: "cornell-box",
+        width: 128,
+        samples: 16,
+        max_depth: 50,
+    },
+    Workload {
+        name: "teapot",
+        width: 320,
+        samples: 4,
+        max_depth: 50,
+    },
+];
+
+/// Timings of the iterations of one workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadResult {
+    pub name: String,
+    /// Width and height of the rendered image.
+    pub size: (usize, usize),
+    /// Render time of every iteration, in seconds.
+    pub times: Vec<f64>,
+    /// Millions of rays per second of every iteration.
+    pub mrays_per_second: Vec<f64>,
+}
+
+impl WorkloadResult {
+    pub fn median_time(&self) -> f64 {
+        median(&self.times)
+    }
+
+    pub fn median_mrays_per_second(&self) -> f64 {
+        median(&self.mrays_per_second)
+    }
+
+    /// Standard deviation of the render times relative to their mean.
+    pub fn time_deviation(&self) -> f64 {
+        let n = self.times.len() as f64;
+        let mean = self.times.iter().sum::<f64>() / n;
+        let variance = self.times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+        variance.sqrt() / mean
+    }
+}
+
+/// Renders `workload` `warmup` times untimed then `iterations` times timed.
+pub fn run(
+    workload: &Workload,
+    threads: usize,
+    chunks: usize,
+    warmup: usize,
+    iterations: usize,
+) -> Result<WorkloadResult> {
+    if iterations == 0 {
+        return Err(Error::InvalidConfig(String::from(
+            "a benchmark needs at least one iteration",
+        )));
+    }
+
+    utils::seed_random(SEED);
+    let scene = scene::find(workload.name)?.build()?;
+    let config = Config::new(
+        scene.aspect_ratio,
+        workload.width,
+        workload.samples,
+        workload.max_depth,
+        SamplerKind::Independent,
+        SEED,
+    );
+    let size = (config.image_width, config.image_height);
+    let mut renderer = Renderer::new(config, scene.world, scene.camera, None);
+
+    let mut result = WorkloadResult {
+        name: workload.name.to_string(),
+        size,
+        times: Vec::with_capacity(iterations),
+        mrays_per_second: Vec::with_capacity(iterations),
+    };
+    for iteration in 0..warmup + iterations {
+        renderer.render_current_frame_threadpool(scene.background, threads, chunks)?;
+        let stats = renderer.take_stats();
+        if iteration >= warmup {
+            result.times.push(stats.wall_time.as_secs_f64());
+            result.mrays_per_second.push(stats.rays_per_second() / 1e6);
+        }
+    }
+    Ok(result)
+}
+
+/// Results of a benchmark run, saved to compare later runs with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Baseline {
+    pub threads: usize,
+    pub results: Vec<WorkloadResult>,
+}
+
+impl Baseline {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "threads": self.threads,
+            "workloads": self.results.iter().map(|r| json!({
+                "name": r.name,
+                "width": r.size.0,
+                "height": r.size.1,
+                "median_time": r.median_time(),
+                "median_mrays_per_second": r.median_mrays_per_second(),
+                "times": r.times,
+                "mrays_per_second": r.mrays_per_second,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let invalid = || Error::InvalidConfig(String::from("malformed benchmark baseline"));
+        let count = |value: &Value| value.as_u64().map(|n| n as usize).ok_or_else(invalid);
+        let numbers = |value: &Value| -> Result<Vec<f64>> {
+            value
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|x| x.as_f64().ok_or_else(invalid))
+                .collect()
+        };
+
+        let results = value["workloads"]
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|w| {
+                Ok(WorkloadResult {
+                    name: w["name"].as_str().ok_or_else(invalid)?.to_string(),
+                    size: (count(&w["width"])?, count(&w["height"])?),
+                    times: numbers(&w["times"])?,
+                    mrays_per_second: numbers(&w["mrays_per_second"])?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Baseline {
+            threads: count(&value["threads"])?,
+            results,
+        })
+    }
+
+    pub fn load(file_path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(file_path)?;
+        let value = serde_json::from_str(&text)
+            .map_err(|e| Error::InvalidConfig(format!("{}: {}", file_path, e)))?;
+        Self::from_json(&value)
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json()).expect("baseline is valid JSON");
+        std::fs::write(file_path, json + "\n")?;
+        Ok(())
+    }
+
+    /// Speed of `result` relative to the baseline of the same workload, -0.1 when it is
+    /// 10% slower. `None` when the baseline does not have the workload.
+    pub fn change(&self, result: &WorkloadResult) -> Option<f64> {
+        let baseline = self.results.iter().find(|r| r.name == result.name)?;
+        Some(result.median_mrays_per_second() / baseline.median_mrays_per_second() - 1.0)
+    }
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{median, Baseline, WorkloadResult};
+
+    fn result(name: &str, mrays_per_second: Vec<f64>) -> WorkloadResult {
+        WorkloadResult {
+            name: name.to_string(),
+            size: (64, 48),
+            times: mrays_per_second.iter().map(|m| 1.0 / m).collect(),
+            mrays_per_second,
+        }
+    }
+
+    #[test]
+    fn should_take_the_middle_value() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+    }
+
+    #[test]
+    fn should_compare_with_a_saved_baseline() {
+        let baseline = Baseline {
+            threads: 4,
+            results: vec![result("cornell-box", vec![2.0, 1.0, 2.5])],
+        };
+        let loaded = Baseline::from_json(&baseline.to_json()).unwrap();
+        assert_eq!(loaded, baseline);
+
+        let slower = result("cornell-box", vec![1.5, 1.5, 1.0]);
+        assert!((loaded.change(&slower).unwrap() + 0.25).abs() < 1e-12);
+        assert_eq!(loaded.change(&result("teapot", vec![1.0])), None);
+    }
+}
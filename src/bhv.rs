@@ -2,7 +2,10 @@ use std::{cmp::Ordering, sync::Arc};
 
 use crate::{
     aabb::Aabb,
+    error::{Error, Result},
     hitable::{HitRecord, Hitable},
+    inspect::Inspector,
+    stats,
     utils::random_int,
 };
 
@@ -18,31 +21,31 @@ impl BHVNode {
         mut src_objects: Vec<Arc<dyn Hitable>>,
         range: (usize, usize),
         time: (f64, f64),
-    ) -> Self {
+    ) -> Result<Self> {
+        if range.1 <= range.0 {
+            return Err(Error::EmptyBvh);
+        }
+        if src_objects[range.0..range.1]
+            .iter()
+            .any(|object| object.bounding_box(time).is_none())
+        {
+            return Err(Error::MissingBoundingBox);
+        }
+
         let axis = random_int(0, 3);
-        let comparator = match axis {
-            0 => Self::box_x_compare,
-            1 => Self::box_y_compare,
-            2 => Self::box_z_compare,
-            _ => {
-                panic!("Axis can only be 0, 1 or 2")
-            }
-        };
+        let comparator =
+            |a: &Arc<dyn Hitable>, b: &Arc<dyn Hitable>| Self::box_compare(a, b, axis, time);
 
         let object_span = range.1 - range.0;
 
-        let mut left;
-        let mut right;
+        let left: Arc<dyn Hitable>;
+        let right: Arc<dyn Hitable>;
 
         if object_span == 1 {
             left = Arc::clone(&src_objects[range.0]);
             right = Arc::clone(&src_objects[range.0]);
         } else if object_span == 2 {
-            if comparator(
-                &Arc::clone(&src_objects[range.0]),
-                &Arc::clone(&src_objects[range.0 + 1]),
-            ) == Ordering::Less
-            {
+            if comparator(&src_objects[range.0], &src_objects[range.0 + 1]) == Ordering::Less {
                 left = Arc::clone(&src_objects[range.0]);
                 right = Arc::clone(&src_objects[range.0 + 1])
             } else {
@@ -52,88 +55,151 @@ impl BHVNode {
         } else {
             src_objects[range.0..range.1].sort_by(comparator);
             let mid = range.0 + object_span / 2;
-            left = Arc::new(BHVNode::new(src_objects.clone(), (range.0, mid), time));
-            right = Arc::new(BHVNode::new(src_objects, (mid, range.1), time));
+            left = Arc::new(BHVNode::new(src_objects.clone(), (range.0, mid), time)?);
+            right = Arc::new(BHVNode::new(src_objects, (mid, range.1), time)?);
         }
 
-        match left.bounding_box(time) {
-            Some(box_left) => match right.bounding_box(time) {
-                Some(box_right) => BHVNode {
-                    left,
-                    right,
-                    some_box: Aabb::surrounding_box(box_left, box_right),
-                },
-                None => panic!("No bounding box in bvh_node constructor"),
-            },
-            None => {
-                panic!("No bounding box in bvh_node constructor")
-            }
-        }
+        let box_left = left.bounding_box(time).ok_or(Error::MissingBoundingBox)?;
+        let box_right = right.bounding_box(time).ok_or(Error::MissingBoundingBox)?;
+
+        Ok(BHVNode {
+            left,
+            right,
+            some_box: Aabb::surrounding_box(box_left, box_right),
+        })
     }
 
-    fn box_compare(a: &Arc<dyn Hitable>, b: &Arc<dyn Hitable>, axis: usize) -> Ordering {
-        match a.bounding_box((0.0, 0.0)) {
-            None => panic!("No bounding box in bvh_node constructor"),
-            Some(box_a) => match b.bounding_box((0.0, 0.0)) {
-                None => panic!("No bounding box in bvh_node constructor"),
-                Some(box_b) => {
-                    if box_a.minimum.at(axis) < box_b.minimum.at(axis) {
-                        Ordering::Less
-                    } else if box_a.minimum.at(axis) > box_b.minimum.at(axis) {
-                        Ordering::Greater
-                    } else {
-                        Ordering::Equal
-                    }
-                }
-            },
+    // Objects are checked for bounding boxes before sorting, missing ones compare equal.
+    // Boxes cover the whole time interval so moving objects are sorted by their sweep
+    fn box_compare(
+        a: &Arc<dyn Hitable>,
+        b: &Arc<dyn Hitable>,
+        axis: usize,
+        time: (f64, f64),
+    ) -> Ordering {
+        match (a.bounding_box(time), b.bounding_box(time)) {
+            (Some(box_a), Some(box_b)) => box_a
+                .minimum
+                .at(axis)
+                .partial_cmp(&box_b.minimum.at(axis))
+                .unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal,
         }
     }
+}
 
-    fn box_x_compare(a: &Arc<dyn Hitable>, b: &Arc<dyn Hitable>) -> Ordering {
-        return Self::box_compare(a, b, 0);
+impl Hitable for BHVNode {
+    // Recusive hit implementation, the right child only needs to beat the left hit
+    fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count_bvh_node();
+        if !self.some_box.andrew_kensler_hit(r, t_min, t_max) {
+            return None;
+        }
+
+        let hit_left = self.left.hit(r, t_min, t_max);
+        let t_max_right = hit_left.as_ref().map_or(t_max, |left_rec| left_rec.t);
+        let hit_right = self.right.hit(r, t_min, t_max_right);
+
+        hit_right.or(hit_left)
     }
 
-    fn box_y_compare(a: &Arc<dyn Hitable>, b: &Arc<dyn Hitable>) -> Ordering {
-        return Self::box_compare(a, b, 1);
+    fn bounding_box(&self, _time: (f64, f64)) -> Option<Aabb> {
+        Some(self.some_box)
     }
 
-    fn box_z_compare(a: &Arc<dyn Hitable>, b: &Arc<dyn Hitable>) -> Ordering {
-        return Self::box_compare(a, b, 2);
+    fn inspect(&self, inspector: &mut Inspector) {
+        if Arc::ptr_eq(&self.left, &self.right) {
+            inspector.bvh_node(&[self.left.as_ref()]);
+        } else {
+            inspector.bvh_node(&[self.left.as_ref(), self.right.as_ref()]);
+        }
     }
 }
 
-impl Hitable for BHVNode {
-    // Recusive hit implementation
-    fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        match self.some_box.hit(r, t_min, t_max) {
-            Some(_hit_record) => {
-                let hit_left = self.left.hit(r, t_min, t_max);
-
-                let t_max_right = if let Some(left_rec) = &hit_left {
-                    left_rec.t
-                } else {
-                    t_max
-                };
-
-                let hit_right = self.right.hit(r, t_min, t_max_right);
-
-                if let Some(left_rec) = hit_left {
-                    return Some(left_rec);
-                }
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::BHVNode;
+    use crate::{
+        animation::{Interpolation, Track, TransformTrack},
+        error::Error,
+        hitable::{animated::Animated, hitable_list::HitableList, sphere::Sphere, Hitable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        utils::{color::Color, seed_random},
+        vec3::Vec3,
+    };
+
+    fn spheres() -> Vec<Arc<dyn Hitable>> {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        (0..20)
+            .map(|i| {
+                let sphere = Sphere::new(
+                    Vec3::new((i % 5) as f64, (i / 5) as f64, -2.0 - (i % 3) as f64),
+                    0.4,
+                    material.clone(),
+                );
+                Arc::new(sphere) as Arc<dyn Hitable>
+            })
+            .collect()
+    }
 
-                if let Some(right_rec) = hit_right {
-                    return Some(right_rec);
-                }
+    #[test]
+    fn should_find_the_same_hits_as_a_list() {
+        seed_random(7);
+        let objects = spheres();
+        let bvh = BHVNode::new(objects.clone(), (0, objects.len()), (0.0, 0.0)).unwrap();
+        let mut list = HitableList::new();
+        for object in objects {
+            list.add_arc(object);
+        }
 
-                return None;
-            }
-            None => {
-                return None;
-            }
+        for i in 0..100 {
+            let target = Vec3::new(i as f64 * 0.05 - 0.5, (i % 7) as f64 * 0.6 - 0.5, -3.0);
+            let r = Ray::new(Vec3::new(2.0, 1.5, 5.0), target - Vec3::new(2.0, 1.5, 5.0));
+            let expected = list.hit(&r, 0.001, f64::INFINITY).map(|rec| rec.t);
+            let found = bvh.hit(&r, 0.001, f64::INFINITY).map(|rec| rec.t);
+            assert_eq!(expected, found);
         }
     }
 
-    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
-        Some(self.some_box)
+    #[test]
+    fn should_bound_moving_objects_over_the_shutter() {
+        let sphere = Sphere::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let translation = Track::new(0.0, Vec3::new(0.0, 0.0, -2.0), Interpolation::Linear).key(
+            1.0,
+            Vec3::new(3.0, 0.0, -2.0),
+            Interpolation::Linear,
+        );
+        let animated: Arc<dyn Hitable> = Arc::new(Animated::new(
+            sphere,
+            TransformTrack::new(
+                translation,
+                Track::constant(Vec3::new(0.0, 0.0, 0.0)),
+                Track::constant(1.0),
+            ),
+        ));
+        let bvh = BHVNode::new(vec![animated.clone(), animated], (0, 2), (0.0, 1.0)).unwrap();
+
+        // Only hit at the end of the shutter, far from where the sphere starts
+        let r = Ray::new_with_time(
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Some(1.0),
+        );
+        assert!(bvh.hit(&r, 0.001, f64::INFINITY).is_some());
+    }
+
+    #[test]
+    fn should_reject_empty_range() {
+        assert!(matches!(
+            BHVNode::new(spheres(), (3, 3), (0.0, 0.0)),
+            Err(Error::EmptyBvh)
+        ));
     }
 }
@@ -1,72 +0,0 @@
-use crate::{
-    ray::Ray,
-    utils::{degrees_to_radians, random_double},
-    vec3::Vec3,
-};
-
-#[derive(Clone)]
-pub struct Camera {
-    origin: Vec3,
-    lower_left_corner: Vec3,
-    horizontal: Vec3,
-    vertical: Vec3,
-    u: Vec3,
-    v: Vec3,
-    w: Vec3,
-    lens_radius: f64,
-    time: Option<(f64, f64)>,
-}
-
-impl Camera {
-    pub fn new(
-        look_from: Vec3,
-        look_at: Vec3,
-        vup: Vec3,
-        vfov: f64,
-        aspect_ratio: f64,
-        appreture: f64,
-        focus_dist: f64,
-        time: Option<(f64, f64)>,
-    ) -> Camera {
-        let theta = degrees_to_radians(vfov);
-        let h = f64::tan(theta / 2.0);
-        let viewport_height = 2.0 * h;
-        let viewport_width = aspect_ratio * viewport_height;
-
-        let w = Vec3::unit_vector(&(look_from - look_at));
-        let u = Vec3::unit_vector(&Vec3::cross(&vup, &w));
-        let v = Vec3::cross(&w, &u);
-
-        let origin = look_from;
-        let horizontal = u * viewport_width * focus_dist;
-        let vertical = v * viewport_height * focus_dist;
-        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - w * focus_dist;
-        let lens_radius = appreture / 2.0;
-
-        Camera {
-            origin,
-            horizontal,
-            vertical,
-            lower_left_corner,
-            u,
-            v,
-            w,
-            lens_radius,
-            time,
-        }
-    }
-
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = Vec3::random_in_unit_disk() * self.lens_radius;
-        let offset = self.u * rd.x + self.v * rd.y;
-
-        Ray::new_with_time(
-            self.origin + offset,
-            self.lower_left_corner + self.horizontal * s + self.vertical * t - self.origin - offset,
-            match self.time {
-                Some(range) => Some(random_double(Some(range))),
-                None => None,
-            },
-        )
-    }
-}
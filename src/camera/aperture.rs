@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use crate::{
+    error::{Error, Result},
+    utils::{degrees_to_radians, PI},
+    vec3::Vec3,
+};
+
+/// Shape of the thin lens opening, which is also the shape of out of focus highlights.
+#[derive(Debug, Clone)]
+pub enum Aperture {
+    /// Perfectly round opening.
+    Disk,
+    /// Regular polygon formed by `blades` straight blades, `rotation` in degrees. Fewer
+    /// than three blades fall back to a disk.
+    Polygon { blades: usize, rotation: f64 },
+    /// Opening transmitting light in proportion to the brightness of an image.
+    Mask(Arc<ApertureMask>),
+}
+
+impl Aperture {
+    /// Maps a point of the unit square to a point of the aperture, which fits in the
+    /// unit disk (or the unit square for masks).
+    pub fn sample(&self, u: (f64, f64)) -> Vec3 {
+        match self {
+            Aperture::Disk | Aperture::Polygon { blades: 0..=2, .. } => {
+                Vec3::sample_in_unit_disk(u)
+            }
+            Aperture::Polygon { blades, rotation } => {
+                Self::sample_polygon(*blades, degrees_to_radians(*rotation), u)
+            }
+            Aperture::Mask(mask) => mask.sample(u),
+        }
+    }
+
+    // Picks one of the triangles between the centre and two adjacent vertices, then
+    // samples it uniformly with the remaining precision of the first dimension
+    fn sample_polygon(blades: usize, rotation: f64, u: (f64, f64)) -> Vec3 {
+        let scaled = u.0 * blades as f64;
+        let triangle = usize::min(scaled as usize, blades - 1);
+        let remapped = scaled - triangle as f64;
+
+        let vertex = |k: usize| {
+            let angle = rotation + 2.0 * PI * k as f64 / blades as f64;
+            Vec3::new(f64::cos(angle), f64::sin(angle), 0.0)
+        };
+
+        let r = f64::sqrt(remapped);
+        (vertex(triangle) * (1.0 - u.1) + vertex(triangle + 1) * u.1) * r
+    }
+}
+
+/// Grayscale aperture image, sampled in proportion to its brightness.
+#[derive(Debug)]
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    // Cumulative brightness of the rows, then of the pixels within every row
+    row_cdf: Vec<f64>,
+    pixel_cdf: Vec<f64>,
+}
+
+impl ApertureMask {
+    /// Loads a mask from an image, black is opaque and white fully transparent.
+    pub fn load(path: &str) -> Result<Self> {
+        let image = image::open(path)?.into_luma8();
+        let values = image.pixels().map(|p| p.0[0] as f64 / 255.0).collect();
+        Self::new(image.width() as usize, image.height() as usize, values)
+    }
+
+    /// Mask from `width * height` transmittances, top row first.
+    pub fn new(width: usize, height: usize, values: Vec<f64>) -> Result<Self> {
+        if width == 0 || height == 0 || values.len() != width * height {
+            return Err(Error::InvalidConfig(format!(
+                "aperture mask needs {}x{} values, got {}",
+                width,
+                height,
+                values.len()
+            )));
+        }
+
+        let mut pixel_cdf = Vec::with_capacity(width * height);
+        let mut row_cdf = Vec::with_capacity(height);
+        let mut total = 0.0;
+        for row in values.chunks(width) {
+            let mut sum = 0.0;
+            for value in row {
+                sum += f64::max(*value, 0.0);
+                pixel_cdf.push(sum);
+            }
+            total += sum;
+            row_cdf.push(total);
+        }
+
+        if total <= 0.0 {
+            return Err(Error::InvalidConfig(String::from(
+                "aperture mask is fully opaque",
+            )));
+        }
+
+        Ok(ApertureMask {
+            width,
+            height,
+            row_cdf,
+            pixel_cdf,
+        })
+    }
+
+    // Finds the first entry of an increasing cdf above u * total, with the position of
+    // u within that entry so the sample can be jittered inside the pixel
+    fn invert(cdf: &[f64], u: f64) -> (usize, f64) {
+        let total = cdf[cdf.len() - 1];
+        let target = u * total;
+        let index = usize::min(cdf.partition_point(|&c| c <= target), cdf.len() - 1);
+        let start = if index == 0 { 0.0 } else { cdf[index - 1] };
+        let width = cdf[index] - start;
+        let offset = if width > 0.0 {
+            ((target - start) / width).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        (index, offset)
+    }
+
+    /// Point of the square [-1, 1] x [-1, 1] picked in proportion to the brightness.
+    pub fn sample(&self, u: (f64, f64)) -> Vec3 {
+        let (row, dy) = Self::invert(&self.row_cdf, u.1);
+        let (column, dx) = Self::invert(
+            &self.pixel_cdf[row * self.width..(row + 1) * self.width],
+            u.0,
+        );
+
+        // Image rows go down, the lens v axis goes up
+        let x = (column as f64 + dx) / self.width as f64;
+        let y = (row as f64 + dy) / self.height as f64;
+        Vec3::new(2.0 * x - 1.0, 1.0 - 2.0 * y, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aperture, ApertureMask};
+    use crate::utils::PI;
+
+    fn grid() -> impl Iterator<Item = (f64, f64)> {
+        (0..16)
+            .flat_map(|i| (0..16).map(move |j| ((i as f64 + 0.5) / 16.0, (j as f64 + 0.5) / 16.0)))
+    }
+
+    #[test]
+    fn should_sample_inside_polygon() {
+        let aperture = Aperture::Polygon {
+            blades: 6,
+            rotation: 0.0,
+        };
+        // Inside every edge, and some samples reach past the inscribed circle
+        let apothem = f64::cos(PI / 6.0);
+        let mut outside_incircle = 0;
+        for u in grid() {
+            let p = aperture.sample(u);
+            for k in 0..6 {
+                let angle = (k as f64 + 0.5) * PI / 3.0;
+                assert!(p.x * angle.cos() + p.y * angle.sin() <= apothem + 1e-9);
+            }
+            if p.length() > apothem {
+                outside_incircle += 1;
+            }
+        }
+        assert!(outside_incircle > 0);
+    }
+
+    #[test]
+    fn should_sample_only_transparent_mask_pixels() {
+        // Only the top right pixel lets light through
+        let mask = ApertureMask::new(2, 2, vec![0.0, 1.0, 0.0, 0.0]).unwrap();
+        for u in grid() {
+            let p = mask.sample(u);
+            assert!(p.x >= 0.0 && p.y >= 0.0, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn should_reject_opaque_mask() {
+        assert!(ApertureMask::new(2, 1, vec![0.0, 0.0]).is_err());
+        assert!(ApertureMask::new(2, 2, vec![1.0]).is_err());
+    }
+}
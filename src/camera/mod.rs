@@ -0,0 +1,670 @@
+pub mod aperture;
+
+use std::sync::Arc;
+
+use crate::{
+    camera::aperture::Aperture,
+    error::{Error, Result},
+    hitable::Hitable,
+    ray::Ray,
+    sampler::Sampler,
+    utils::{degrees_to_radians, INFINITY, PI},
+    vec3::Vec3,
+};
+
+/// How the image plane maps to ray directions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Pinhole or thin lens perspective, the default.
+    Perspective,
+    /// Parallel rays, the viewport at the focus distance keeps its size.
+    Orthographic,
+    /// Circular fisheye inscribed in the image height, `fov` is the circle's diameter
+    /// in degrees. Pixels outside the circle are black.
+    Fisheye { fov: f64, mapping: FisheyeMapping },
+    /// Full 360° by 180° latitude-longitude panorama, best rendered at a 2:1 aspect ratio.
+    Equirectangular,
+}
+
+/// Radial mapping of a fisheye lens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FisheyeMapping {
+    /// Distance from the centre proportional to the angle, r = f θ.
+    Equidistant,
+    /// Preserves solid angles, r = 2f sin(θ / 2).
+    Equisolid,
+}
+
+/// Projection names for the command line, fisheyes take their field of view separately.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionKind {
+    Perspective,
+    Orthographic,
+    Equidistant,
+    Equisolid,
+    Equirectangular,
+}
+
+impl ProjectionKind {
+    pub fn projection(&self, fisheye_fov: f64) -> Projection {
+        match self {
+            ProjectionKind::Perspective => Projection::Perspective,
+            ProjectionKind::Orthographic => Projection::Orthographic,
+            ProjectionKind::Equidistant => Projection::Fisheye {
+                fov: fisheye_fov,
+                mapping: FisheyeMapping::Equidistant,
+            },
+            ProjectionKind::Equisolid => Projection::Fisheye {
+                fov: fisheye_fov,
+                mapping: FisheyeMapping::Equisolid,
+            },
+            ProjectionKind::Equirectangular => Projection::Equirectangular,
+        }
+    }
+}
+
+/// How the shutter lets light in while it is open, which weights the times of the rays.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutterShape {
+    /// Fully open for the whole interval.
+    Box,
+    /// Opens and closes progressively, fully open at the middle of the interval.
+    Triangle,
+}
+
+impl ShutterShape {
+    /// Maps a uniform sample of [0, 1) to a fraction of the shutter interval.
+    pub fn warp(&self, u: f64) -> f64 {
+        match self {
+            ShutterShape::Box => u,
+            ShutterShape::Triangle if u < 0.5 => f64::sqrt(2.0 * u) / 2.0,
+            ShutterShape::Triangle => 1.0 - f64::sqrt(2.0 * (1.0 - u)) / 2.0,
+        }
+    }
+}
+
+/// Thin lens camera with optional shutter interval for motion blur.
+#[derive(Clone)]
+pub struct Camera {
+    origin: Vec3,
+    lower_left_corner: Vec3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    lens_radius: f64,
+    focus_dist: f64,
+    aspect_ratio: f64,
+    time: Option<(f64, f64)>,
+    projection: Projection,
+    eye_offset: f64,
+    convergence: f64,
+    aperture: Aperture,
+    cat_eye: f64,
+    shutter_shape: ShutterShape,
+    motion: Option<Arc<[(f64, Camera)]>>,
+}
+
+impl Camera {
+    /// Camera at `look_from` pointing at `look_at`, `vfov` is the vertical field of view
+    /// in degrees and objects at `focus_dist` are in focus. `time` is the shutter
+    /// interval, `None` for a static scene.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        look_from: Vec3,
+        look_at: Vec3,
+        vup: Vec3,
+        vfov: f64,
+        aspect_ratio: f64,
+        appreture: f64,
+        focus_dist: f64,
+        time: Option<(f64, f64)>,
+    ) -> Camera {
+        let theta = degrees_to_radians(vfov);
+        let h = f64::tan(theta / 2.0);
+        let viewport_height = 2.0 * h;
+        let viewport_width = aspect_ratio * viewport_height;
+
+        let w = Vec3::unit_vector(&(look_from - look_at));
+        let u = Vec3::unit_vector(&Vec3::cross(&vup, &w));
+        let v = Vec3::cross(&w, &u);
+
+        let origin = look_from;
+        let horizontal = u * viewport_width * focus_dist;
+        let vertical = v * viewport_height * focus_dist;
+        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - w * focus_dist;
+        let lens_radius = appreture / 2.0;
+
+        Camera {
+            origin,
+            horizontal,
+            vertical,
+            lower_left_corner,
+            u,
+            v,
+            w,
+            lens_radius,
+            focus_dist,
+            aspect_ratio,
+            time,
+            projection: Projection::Perspective,
+            eye_offset: 0.0,
+            convergence: focus_dist,
+            aperture: Aperture::Disk,
+            cat_eye: 0.0,
+            shutter_shape: ShutterShape::Box,
+            motion: None,
+        }
+    }
+
+    /// Same camera with another projection. The thin lens only applies to perspective
+    /// and orthographic projections, the shutter interval applies to all of them.
+    pub fn with_projection(mut self, projection: Projection) -> Camera {
+        self.projection = projection;
+        self
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Same camera moved `offset` along its right axis for one eye of a stereo pair, both
+    /// eyes see objects at `convergence` in the same place. Perspective eyes use off-axis
+    /// frustums, equirectangular eyes follow omnidirectional stereo and rotate their
+    /// offset with the viewing direction.
+    pub fn with_eye(mut self, offset: f64, convergence: f64) -> Camera {
+        self.eye_offset = offset;
+        self.convergence = convergence;
+        self
+    }
+
+    /// Same camera with another lens opening shape, scaled to the aperture diameter.
+    pub fn with_aperture(mut self, aperture: Aperture) -> Camera {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Same camera with optical vignetting: the lens barrel clips the aperture of points
+    /// away from the centre of the frame, darkening the edges and giving out of focus
+    /// highlights a cat-eye shape. `strength` is how far the disc clipping the aperture is
+    /// offset in the corners of the frame, in aperture radii, and less toward the centre:
+    /// 0 disables it, at 1 the corners see about 39% of the aperture, from 2 none of it.
+    pub fn with_cat_eye(mut self, strength: f64) -> Camera {
+        self.cat_eye = strength;
+        self
+    }
+
+    /// Same camera with another shutter interval, `None` for a static scene.
+    pub fn with_shutter(mut self, time: Option<(f64, f64)>) -> Camera {
+        self.time = time;
+        self
+    }
+
+    pub fn with_shutter_shape(mut self, shutter_shape: ShutterShape) -> Camera {
+        self.shutter_shape = shutter_shape;
+        self
+    }
+
+    /// Same camera moving while the shutter is open. `keyframes` are the cameras at
+    /// increasing times, rays are interpolated between the two around their time.
+    pub fn with_motion(mut self, keyframes: Vec<(f64, Camera)>) -> Camera {
+        self.motion = Some(keyframes.into());
+        self
+    }
+
+    /// Same camera focused on whatever the viewport point `(s, t)` sees in `world`,
+    /// at the opening of the shutter. The focus is unchanged when the ray escapes. Only
+    /// the thin lens of perspective and orthographic cameras has a focus.
+    pub fn autofocus(mut self, world: &dyn Hitable, s: f64, t: f64) -> Result<Camera> {
+        if !self.has_thin_lens() {
+            return Err(Error::InvalidConfig(String::from(
+                "autofocus needs a perspective or orthographic camera",
+            )));
+        }
+        // The ray through the centre of the lens
+        let Some((origin, direction)) = self.project(s, t, Vec3::new(0.0, 0.0, 0.0)) else {
+            return Ok(self);
+        };
+        let r = Ray::new_with_time(origin, direction, self.time.map(|time| time.0));
+
+        if let Some(rec) = world.hit(&r, 0.001, INFINITY) {
+            // Distance of the focus plane, not of the hit point
+            let focus_dist = Vec3::dot(&(rec.p - self.origin), &-self.w);
+            if focus_dist > 0.0 {
+                self.place(self.origin, focus_dist);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Same camera moved back or forth along its view direction so the bounding sphere
+    /// of `hitable` fits the view, and focused on its centre. Orthographic cameras are
+    /// resized to it instead, equirectangular ones see everything and cannot be framed.
+    pub fn frame(mut self, hitable: &dyn Hitable) -> Result<Camera> {
+        let bounds = hitable
+            .bounding_box(self.time.unwrap_or((0.0, 0.0)))
+            .ok_or(Error::MissingBoundingBox)?;
+        let centre = (bounds.minimum + bounds.maximum) / 2.0;
+        let radius = (bounds.maximum - bounds.minimum).length() / 2.0;
+
+        let narrowest = f64::min(self.horizontal.length(), self.vertical.length());
+        let distance = match self.projection {
+            Projection::Perspective => {
+                let tan_half_fov = narrowest / (2.0 * self.focus_dist);
+                radius / f64::sin(f64::atan(tan_half_fov))
+            }
+            Projection::Orthographic => {
+                // The narrowest side of the viewport spans the sphere, seen from outside
+                let scale = 2.0 * radius / narrowest;
+                self.horizontal = self.horizontal * scale;
+                self.vertical = self.vertical * scale;
+                2.0 * radius
+            }
+            // The circle is inscribed in the image height, past 180 degrees the sphere
+            // fits from as close as its radius
+            Projection::Fisheye { fov, .. } => {
+                radius / f64::sin(degrees_to_radians(fov.min(180.0)) / 2.0)
+            }
+            Projection::Equirectangular => {
+                return Err(Error::InvalidConfig(String::from(
+                    "an equirectangular camera cannot be framed",
+                )))
+            }
+        };
+
+        self.place(centre + self.w * distance, distance);
+        Ok(self)
+    }
+
+    fn has_thin_lens(&self) -> bool {
+        matches!(
+            self.projection,
+            Projection::Perspective | Projection::Orthographic
+        )
+    }
+
+    // Moves the camera and its focus plane, keeping the field of view. Orthographic
+    // cameras keep their viewport size instead.
+    fn place(&mut self, origin: Vec3, focus_dist: f64) {
+        if self.projection != Projection::Orthographic {
+            self.horizontal = self.horizontal * (focus_dist / self.focus_dist);
+            self.vertical = self.vertical * (focus_dist / self.focus_dist);
+        }
+        if self.convergence == self.focus_dist {
+            self.convergence = focus_dist;
+        }
+        self.origin = origin;
+        self.focus_dist = focus_dist;
+        self.lower_left_corner =
+            origin - self.horizontal / 2.0 - self.vertical / 2.0 - self.w * focus_dist;
+    }
+
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    pub fn focus_dist(&self) -> f64 {
+        self.focus_dist
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn vfov(&self) -> f64 {
+        2.0 * f64::atan(self.vertical.length() / (2.0 * self.focus_dist)).to_degrees()
+    }
+
+    pub fn lens_radius(&self) -> f64 {
+        self.lens_radius
+    }
+
+    pub fn time(&self) -> Option<(f64, f64)> {
+        self.time
+    }
+
+    /// Right, up and backward unit vectors of the camera.
+    pub fn basis(&self) -> (Vec3, Vec3, Vec3) {
+        (self.u, self.v, self.w)
+    }
+
+    /// Ray through the viewport point `(s, t)`, both in [0, 1] from the lower left corner.
+    /// `None` when the point is outside of the projection, like the corners of a fisheye,
+    /// or when the lens sample is blocked by the barrel.
+    pub fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        // Lens and time samples are always drawn so every projection uses the same dimensions
+        let lens = self.aperture.sample(sampler.get_2d());
+        let time_sample = sampler.get_1d();
+        let time = self
+            .time
+            .map(|range| range.0 + (range.1 - range.0) * self.shutter_shape.warp(time_sample));
+
+        let (origin, direction) = match (&self.motion, time) {
+            (Some(motion), Some(time)) => Self::moving_ray(motion, s, t, lens, time)?,
+            _ => self.project(s, t, lens)?,
+        };
+        Some(Ray::new_with_time(origin, direction, time))
+    }
+
+    // Blends the rays of the cameras before and after `time`
+    fn moving_ray(
+        motion: &[(f64, Camera)],
+        s: f64,
+        t: f64,
+        lens: Vec3,
+        time: f64,
+    ) -> Option<(Vec3, Vec3)> {
+        let next = motion.partition_point(|(key_time, _)| *key_time <= time);
+        if next == 0 {
+            return motion[0].1.project(s, t, lens);
+        }
+        if next == motion.len() {
+            return motion[next - 1].1.project(s, t, lens);
+        }
+
+        let (time0, camera0) = &motion[next - 1];
+        let (time1, camera1) = &motion[next];
+        let blend = (time - time0) / (time1 - time0);
+        let (origin0, direction0) = camera0.project(s, t, lens)?;
+        let (origin1, direction1) = camera1.project(s, t, lens)?;
+        Some((
+            origin0 * (1.0 - blend) + origin1 * blend,
+            Vec3::unit_vector(&direction0) * (1.0 - blend) + Vec3::unit_vector(&direction1) * blend,
+        ))
+    }
+
+    // Origin and direction of the ray through `(s, t)` from the point `lens` of the aperture
+    fn project(&self, s: f64, t: f64, lens: Vec3) -> Option<(Vec3, Vec3)> {
+        let rd = lens * self.lens_radius;
+        let offset = self.u * rd.x + self.v * rd.y;
+        let eye = self.u * self.eye_offset;
+
+        // The barrel is a second unit circle on the lens, shifted with the image position
+        if self.has_thin_lens() && self.cat_eye > 0.0 {
+            let x = (2.0 * s - 1.0) * self.aspect_ratio;
+            let y = 2.0 * t - 1.0;
+            let scale = self.cat_eye / f64::sqrt(self.aspect_ratio * self.aspect_ratio + 1.0);
+            let barrel = Vec3::new(x * scale, y * scale, 0.0);
+            if (lens - barrel).length_squared() > 1.0 {
+                return None;
+            }
+        }
+
+        let (origin, direction) = match self.projection {
+            Projection::Perspective => (
+                self.origin + eye + offset,
+                self.lower_left_corner + self.horizontal * s + self.vertical * t
+                    - self.origin
+                    - eye * (self.focus_dist / self.convergence)
+                    - offset,
+            ),
+            Projection::Orthographic => {
+                let focus_point = self.lower_left_corner + self.horizontal * s + self.vertical * t;
+                let plane_point = focus_point + self.w * self.focus_dist;
+                (
+                    plane_point + eye + offset,
+                    focus_point - plane_point - offset,
+                )
+            }
+            Projection::Fisheye { fov, mapping } => {
+                let x = (2.0 * s - 1.0) * self.aspect_ratio;
+                let y = 2.0 * t - 1.0;
+                let r = f64::sqrt(x * x + y * y);
+                if r > 1.0 {
+                    return None;
+                }
+
+                let theta_max = degrees_to_radians(fov) / 2.0;
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => r * theta_max,
+                    FisheyeMapping::Equisolid => 2.0 * f64::asin(r * f64::sin(theta_max / 2.0)),
+                };
+                let phi = f64::atan2(y, x);
+                let direction = self.direction(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                (self.origin + eye, direction * self.convergence - eye)
+            }
+            Projection::Equirectangular => {
+                let phi = (s - 0.5) * 2.0 * PI;
+                let latitude = (t - 0.5) * PI;
+                let direction = self.direction(
+                    latitude.cos() * phi.sin(),
+                    latitude.sin(),
+                    latitude.cos() * phi.cos(),
+                );
+                // The eyes sit on a circle, always to the side of the horizontal direction
+                let eye = self.direction(phi.cos(), 0.0, -phi.sin()) * self.eye_offset;
+                (self.origin + eye, direction * self.convergence - eye)
+            }
+        };
+
+        Some((origin, direction))
+    }
+
+    // Direction in camera space, x right, y up and z forward
+    fn direction(&self, x: f64, y: f64, z: f64) -> Vec3 {
+        self.u * x + self.v * y - self.w * z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Camera, FisheyeMapping, Projection, ShutterShape};
+    use std::sync::Arc;
+
+    use crate::{
+        hitable::sphere::Sphere, material::lambertian::Lambertian, ray::Ray, sampler::SamplerKind,
+        utils::color::Color, vec3::Vec3,
+    };
+
+    fn camera(projection: Projection) -> Camera {
+        Camera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            90.0,
+            2.0,
+            0.0,
+            1.0,
+            None,
+        )
+        .with_projection(projection)
+    }
+
+    fn direction(camera: &Camera, s: f64, t: f64) -> Option<Vec3> {
+        let mut sampler = SamplerKind::Independent.build(1, 0);
+        sampler.start_pixel_sample((0, 0), 0);
+        camera
+            .get_ray(s, t, sampler.as_mut())
+            .map(|r| Vec3::unit_vector(&r.direction))
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn should_look_forward_through_the_centre() {
+        for projection in [
+            Projection::Perspective,
+            Projection::Orthographic,
+            Projection::Fisheye {
+                fov: 180.0,
+                mapping: FisheyeMapping::Equidistant,
+            },
+            Projection::Equirectangular,
+        ] {
+            let d = direction(&camera(projection), 0.5, 0.5).unwrap();
+            assert_close(d, Vec3::new(0.0, 0.0, -1.0));
+        }
+    }
+
+    #[test]
+    fn should_cast_parallel_orthographic_rays() {
+        let camera = camera(Projection::Orthographic);
+        assert_close(
+            direction(&camera, 0.0, 0.0).unwrap(),
+            direction(&camera, 1.0, 1.0).unwrap(),
+        );
+    }
+
+    #[test]
+    fn should_skip_fisheye_corners() {
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let camera = camera(Projection::Fisheye {
+                fov: 180.0,
+                mapping,
+            });
+            assert!(direction(&camera, 0.0, 0.0).is_none());
+            // The top of the circle is 90 degrees off axis
+            assert_close(
+                direction(&camera, 0.5, 1.0).unwrap(),
+                Vec3::new(0.0, 1.0, 0.0),
+            );
+        }
+    }
+
+    #[test]
+    fn should_converge_stereo_eyes() {
+        for projection in [Projection::Perspective, Projection::Equirectangular] {
+            let left = camera(projection).with_eye(-0.1, 2.0);
+            let right = camera(projection).with_eye(0.1, 2.0);
+            let mut sampler = SamplerKind::Independent.build(1, 0);
+            sampler.start_pixel_sample((0, 0), 0);
+            let l = left.get_ray(0.6, 0.4, sampler.as_mut()).unwrap();
+            let r = right.get_ray(0.6, 0.4, sampler.as_mut()).unwrap();
+            assert!((l.origin - r.origin).length() > 0.1);
+
+            // Both rays cross the convergence distance at the same point
+            let at_convergence = |ray: &Ray| {
+                let d = Vec3::unit_vector(&ray.direction);
+                ray.origin + d * (-2.0 / d.z)
+            };
+            if projection == Projection::Perspective {
+                assert_close(at_convergence(&l), at_convergence(&r));
+            } else {
+                assert_close(l.origin + l.direction, r.origin + r.direction);
+            }
+        }
+    }
+
+    #[test]
+    fn should_autofocus_on_the_centre_pixel() {
+        let sphere = Sphere::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let unfocused = camera(Projection::Perspective);
+        let focused = unfocused.clone().autofocus(&sphere, 0.5, 0.5).unwrap();
+        assert!((focused.focus_dist() - 4.0).abs() < 1e-6);
+        // The field of view is kept
+        assert_close(
+            direction(&focused, 1.0, 0.0).unwrap(),
+            direction(&unfocused, 1.0, 0.0).unwrap(),
+        );
+    }
+
+    #[test]
+    fn should_frame_bounding_box() {
+        let sphere = Sphere::new(
+            Vec3::new(3.0, 0.0, -10.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let camera = camera(Projection::Perspective).frame(&sphere).unwrap();
+        let origin = camera.origin();
+        // Still looking down -z, straight at the sphere
+        assert!((origin.x - 3.0).abs() < 1e-9 && origin.y.abs() < 1e-9);
+        assert!(origin.z > -10.0);
+        assert_close(
+            direction(&camera, 0.5, 0.5).unwrap(),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert!((camera.focus_dist() - (origin.z + 10.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_frame_bounding_box_with_orthographic_view() {
+        let centre = Vec3::new(3.0, 0.0, -10.0);
+        let sphere = Sphere::new(
+            centre,
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        // Half of the diagonal of the bounding box of the sphere
+        let radius = f64::sqrt(3.0);
+        let camera = camera(Projection::Orthographic).frame(&sphere).unwrap();
+        assert!((camera.origin() - centre).length() > radius);
+        assert!((camera.focus_dist() - (camera.origin() - centre).length()).abs() < 1e-9);
+
+        // Rays through the top and bottom edges, the narrowest side, touch the sphere
+        let mut sampler = SamplerKind::Independent.build(1, 0);
+        for t in [0.0, 1.0] {
+            sampler.start_pixel_sample((0, 0), 0);
+            let r = camera.get_ray(0.5, t, sampler.as_mut()).unwrap();
+            let d = Vec3::unit_vector(&r.direction);
+            let to_centre = centre - r.origin;
+            let distance = (to_centre - d * Vec3::dot(&to_centre, &d)).length();
+            assert!((distance - radius).abs() < 1e-9, "{}", distance);
+        }
+
+        let panorama = camera.with_projection(Projection::Equirectangular);
+        assert!(panorama.clone().frame(&sphere).is_err());
+        assert!(panorama.autofocus(&sphere, 0.5, 0.5).is_err());
+    }
+
+    #[test]
+    fn should_weight_triangle_shutter_toward_the_middle() {
+        let shape = ShutterShape::Triangle;
+        assert_eq!(shape.warp(0.0), 0.0);
+        assert!((shape.warp(0.5) - 0.5).abs() < 1e-12);
+        assert!((shape.warp(0.125) - 0.25).abs() < 1e-12);
+        assert!((shape.warp(0.875) - 0.75).abs() < 1e-12);
+        assert_eq!(ShutterShape::Box.warp(0.125), 0.125);
+    }
+
+    #[test]
+    fn should_move_camera_during_the_shutter() {
+        let at = |x: f64| {
+            Camera::new(
+                Vec3::new(x, 0.0, 0.0),
+                Vec3::new(x, 0.0, -1.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                90.0,
+                1.0,
+                0.0,
+                1.0,
+                Some((0.0, 1.0)),
+            )
+        };
+        let camera = at(0.0).with_motion(vec![(0.0, at(0.0)), (1.0, at(2.0))]);
+
+        let mut sampler = SamplerKind::Independent.build(1, 0);
+        for i in 0..8 {
+            sampler.start_pixel_sample((0, 0), i);
+            let r = camera.get_ray(0.5, 0.5, sampler.as_mut()).unwrap();
+            let time = r.time.unwrap();
+            assert!((r.origin.x - 2.0 * time).abs() < 1e-9);
+            assert_close(Vec3::unit_vector(&r.direction), Vec3::new(0.0, 0.0, -1.0));
+        }
+    }
+
+    #[test]
+    fn should_wrap_equirectangular_around() {
+        let camera = camera(Projection::Equirectangular);
+        assert_close(
+            direction(&camera, 0.0, 0.5).unwrap(),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        assert_close(
+            direction(&camera, 0.75, 0.5).unwrap(),
+            Vec3::new(1.0, 0.0, 0.0),
+        );
+        assert_close(
+            direction(&camera, 0.5, 1.0).unwrap(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! NVIDIA's FLIP image difference evaluator, see Andersson et al. "FLIP: A Difference
+//! Evaluator for Alternating Images" and the HDR extension "Visualizing Errors in
+//! Rendered High Dynamic Range Images".
+
+use crate::{
+    compare::{convolve_separable, luminance, FloatImage},
+    utils::{color::Color, PI},
+};
+
+// Pixels per degree of visual angle for a 0.7m wide 4K monitor seen from 0.7m
+pub const DEFAULT_PIXELS_PER_DEGREE: f64 = 67.0;
+
+const QC: f64 = 0.7;
+const QF: f64 = 0.5;
+const PC: f64 = 0.4;
+const PT: f64 = 0.95;
+const FEATURE_WIDTH: f64 = 0.082;
+
+// Sums of Gaussians (a1, b1, a2, b2) modelling the contrast sensitivity of the
+// achromatic, red-green and blue-yellow channels
+const CSF: [(f64, f64, f64, f64); 3] = [
+    (1.0, 0.0047, 0.0, 1e-5),
+    (1.0, 0.0053, 0.0, 1e-5),
+    (34.1, 0.04, 13.5, 0.025),
+];
+
+// ACES filmic tone mapper with the 0.6 exposure of the reference implementation folded in
+const ACES: [f64; 6] = [
+    0.6 * 0.6 * 2.51,
+    0.6 * 0.03,
+    0.0,
+    0.6 * 0.6 * 2.43,
+    0.6 * 0.59,
+    0.14,
+];
+
+fn linear_rgb_to_xyz(c: &Color) -> (f64, f64, f64) {
+    (
+        0.4124564 * c.r + 0.3575761 * c.g + 0.1804375 * c.b,
+        0.2126729 * c.r + 0.7151522 * c.g + 0.0721750 * c.b,
+        0.0193339 * c.r + 0.1191920 * c.g + 0.9503041 * c.b,
+    )
+}
+
+fn xyz_to_linear_rgb(x: f64, y: f64, z: f64) -> Color {
+    Color::new(
+        3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+        0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    )
+}
+
+fn reference_white() -> (f64, f64, f64) {
+    linear_rgb_to_xyz(&Color::new(1.0, 1.0, 1.0))
+}
+
+fn linear_rgb_to_ycxcz(c: &Color) -> [f64; 3] {
+    let (x, y, z) = linear_rgb_to_xyz(c);
+    let (xn, yn, zn) = reference_white();
+    [
+        116.0 * y / yn - 16.0,
+        500.0 * (x / xn - y / yn),
+        200.0 * (y / yn - z / zn),
+    ]
+}
+
+fn ycxcz_to_linear_rgb(c: [f64; 3]) -> Color {
+    let (xn, yn, zn) = reference_white();
+    let y = (c[0] + 16.0) / 116.0;
+    xyz_to_linear_rgb(xn * (c[1] / 500.0 + y), yn * y, zn * (y - c[2] / 200.0))
+}
+
+// CIELAB with the Hunt adjustment applied to the chromatic channels
+fn linear_rgb_to_hunt_lab(c: &Color) -> [f64; 3] {
+    let f = |t: f64| {
+        let delta: f64 = 6.0 / 29.0;
+        if t > delta.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * delta * delta) + 4.0 / 29.0
+        }
+    };
+
+    let (x, y, z) = linear_rgb_to_xyz(c);
+    let (xn, yn, zn) = reference_white();
+    let l = 116.0 * f(y / yn) - 16.0;
+    let a = 500.0 * (f(x / xn) - f(y / yn));
+    let b = 200.0 * (f(y / yn) - f(z / zn));
+    [l, 0.01 * l * a, 0.01 * l * b]
+}
+
+fn hyab(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (a[0] - b[0]).abs() + ((a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+fn csf_filter(image: &FloatImage, pixels_per_degree: f64) -> Vec<Color> {
+    let max_b = CSF
+        .iter()
+        .map(|(_, b1, _, b2)| b1.max(*b2))
+        .fold(0.0, f64::max);
+    let radius = (3.0 * (max_b / (2.0 * PI * PI)).sqrt() * pixels_per_degree).ceil() as isize;
+
+    let ycxcz: Vec<[f64; 3]> = image.pixels.iter().map(linear_rgb_to_ycxcz).collect();
+    let mut filtered = vec![[0.0; 3]; ycxcz.len()];
+
+    for (channel, (a1, b1, a2, b2)) in CSF.iter().enumerate() {
+        let values: Vec<f64> = ycxcz.iter().map(|c| c[channel]).collect();
+
+        // Each Gaussian of the sum is separable, filter with them one at a time and
+        // normalise by the sum of the full 2D kernel
+        let mut terms = Vec::new();
+        let mut total = 0.0;
+        for (a, b) in [(a1, b1), (a2, b2)] {
+            if *a == 0.0 {
+                continue;
+            }
+            let kernel: Vec<f64> = (-radius..=radius)
+                .map(|x| {
+                    let d = x as f64 / pixels_per_degree;
+                    f64::exp(-PI * PI * d * d / b)
+                })
+                .collect();
+            let scale = a * (PI / b).sqrt();
+            total += scale * kernel.iter().sum::<f64>().powi(2);
+            terms.push((scale, kernel));
+        }
+
+        for (scale, kernel) in terms {
+            let term = convolve_separable(&values, image.width, image.height, &kernel, &kernel);
+            for (f, t) in filtered.iter_mut().zip(term) {
+                f[channel] += scale / total * t;
+            }
+        }
+    }
+
+    filtered
+        .into_iter()
+        .map(|c| {
+            let rgb = ycxcz_to_linear_rgb(c);
+            Color::new(
+                rgb.r.clamp(0.0, 1.0),
+                rgb.g.clamp(0.0, 1.0),
+                rgb.b.clamp(0.0, 1.0),
+            )
+        })
+        .collect()
+}
+
+// Magnitudes of the edge and point features of the normalised luminance
+fn features(image: &FloatImage, pixels_per_degree: f64) -> (Vec<f64>, Vec<f64>) {
+    let sd = 0.5 * FEATURE_WIDTH * pixels_per_degree;
+    let radius = (3.0 * sd).ceil() as isize;
+
+    let gaussian: Vec<f64> = (-radius..=radius)
+        .map(|x| f64::exp(-((x * x) as f64) / (2.0 * sd * sd)))
+        .collect();
+    let gaussian_sum: f64 = gaussian.iter().sum();
+    let gaussian: Vec<f64> = gaussian.iter().map(|g| g / gaussian_sum).collect();
+
+    // Positive and negative weights are normalised to sum to 1 and -1 independently
+    let normalised = |kernel: Vec<f64>| -> Vec<f64> {
+        let positive: f64 = kernel.iter().filter(|k| **k > 0.0).sum();
+        let negative: f64 = -kernel.iter().filter(|k| **k < 0.0).sum::<f64>();
+        kernel
+            .iter()
+            .map(|k| if *k > 0.0 { k / positive } else { k / negative })
+            .collect()
+    };
+    let edge = normalised(
+        (-radius..=radius)
+            .zip(&gaussian)
+            .map(|(x, g)| -(x as f64) * g)
+            .collect(),
+    );
+    let point = normalised(
+        (-radius..=radius)
+            .zip(&gaussian)
+            .map(|(x, g)| ((x * x) as f64 / (sd * sd) - 1.0) * g)
+            .collect(),
+    );
+
+    let y: Vec<f64> = image
+        .pixels
+        .iter()
+        .map(|c| (linear_rgb_to_ycxcz(c)[0] + 16.0) / 116.0)
+        .collect();
+    let magnitude = |kernel: &[f64]| -> Vec<f64> {
+        let dx = convolve_separable(&y, image.width, image.height, kernel, &gaussian);
+        let dy = convolve_separable(&y, image.width, image.height, &gaussian, kernel);
+        dx.iter().zip(dy).map(|(x, y)| x.hypot(y)).collect()
+    };
+
+    (magnitude(&edge), magnitude(&point))
+}
+
+/// Per pixel LDR-FLIP error in [0, 1] between two images of linear values in [0, 1].
+pub fn ldr_flip(reference: &FloatImage, test: &FloatImage, pixels_per_degree: f64) -> Vec<f64> {
+    let reference_filtered = csf_filter(reference, pixels_per_degree);
+    let test_filtered = csf_filter(test, pixels_per_degree);
+
+    let green = linear_rgb_to_hunt_lab(&Color::new(0.0, 1.0, 0.0));
+    let blue = linear_rgb_to_hunt_lab(&Color::new(0.0, 0.0, 1.0));
+    let cmax = hyab(&green, &blue).powf(QC);
+    let pccmax = PC * cmax;
+
+    let (reference_edges, reference_points) = features(reference, pixels_per_degree);
+    let (test_edges, test_points) = features(test, pixels_per_degree);
+
+    (0..reference.pixels.len())
+        .map(|i| {
+            let delta = hyab(
+                &linear_rgb_to_hunt_lab(&reference_filtered[i]),
+                &linear_rgb_to_hunt_lab(&test_filtered[i]),
+            )
+            .powf(QC);
+            let color_error = if delta < pccmax {
+                PT / pccmax * delta
+            } else {
+                PT + (delta - pccmax) / (cmax - pccmax) * (1.0 - PT)
+            };
+
+            let feature_error = f64::max(
+                (reference_edges[i] - test_edges[i]).abs(),
+                (reference_points[i] - test_points[i]).abs(),
+            );
+            let feature_error = (feature_error / 2.0_f64.sqrt()).powf(QF);
+
+            color_error.powf(1.0 - feature_error)
+        })
+        .collect()
+}
+
+fn tone_map(x: f64) -> f64 {
+    let k = ACES;
+    ((x * (k[0] * x + k[1]) + k[2]) / (x * (k[3] * x + k[4]) + k[5])).clamp(0.0, 1.0)
+}
+
+/// Per pixel HDR-FLIP error, the maximum LDR-FLIP error over a range of exposures
+/// chosen from the luminance of the reference.
+pub fn hdr_flip(reference: &FloatImage, test: &FloatImage, pixels_per_degree: f64) -> Vec<f64> {
+    // Exposure at which the tone mapper reaches 0.85, solved from its quadratic form
+    let t = 0.85;
+    let (a, b, c) = (
+        ACES[0] - t * ACES[3],
+        ACES[1] - t * ACES[4],
+        ACES[2] - t * ACES[5],
+    );
+    let x_max = (-b + (b * b - 4.0 * a * c).sqrt()) / (2.0 * a);
+
+    let mut luminances: Vec<f64> = reference
+        .pixels
+        .iter()
+        .map(luminance)
+        .filter(|y| *y > 0.0)
+        .collect();
+    luminances.sort_by(|a, b| a.total_cmp(b));
+
+    let (start, stop) = match (luminances.get(luminances.len() / 2), luminances.last()) {
+        (Some(median), Some(max)) => ((x_max / max).log2(), (x_max / median).log2()),
+        _ => (0.0, 0.0),
+    };
+    let exposures = usize::max(2, (stop - start).ceil() as usize);
+
+    let mut errors = vec![0.0; reference.pixels.len()];
+    for i in 0..exposures {
+        let exposure = start + (stop - start) * i as f64 / (exposures - 1) as f64;
+        let scale = 2.0_f64.powf(exposure);
+
+        let exposed = |image: &FloatImage| image.map(|x| tone_map(x * scale));
+        let ldr = ldr_flip(&exposed(reference), &exposed(test), pixels_per_degree);
+
+        for (e, l) in errors.iter_mut().zip(ldr) {
+            *e = f64::max(*e, l);
+        }
+    }
+    errors
+}
+
+/// Magma colour map used for error maps, `t` in [0, 1].
+pub fn magma(t: f64) -> Color {
+    const STOPS: [(f64, f64, f64); 9] = [
+        (0.0, 0.0, 4.0),
+        (28.0, 16.0, 68.0),
+        (79.0, 18.0, 123.0),
+        (129.0, 37.0, 129.0),
+        (181.0, 54.0, 122.0),
+        (229.0, 80.0, 100.0),
+        (251.0, 135.0, 97.0),
+        (254.0, 194.0, 135.0),
+        (252.0, 253.0, 191.0),
+    ];
+
+    let x = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let i = usize::min(x as usize, STOPS.len() - 2);
+    let f = x - i as f64;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+
+    Color::new(
+        (a.0 + (b.0 - a.0) * f) / 255.0,
+        (a.1 + (b.1 - a.1) * f) / 255.0,
+        (a.2 + (b.2 - a.2) * f) / 255.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ldr_flip, DEFAULT_PIXELS_PER_DEGREE};
+    use crate::{compare::FloatImage, utils::color::Color};
+
+    fn uniform(c: Color) -> FloatImage {
+        FloatImage {
+            width: 8,
+            height: 8,
+            pixels: vec![c; 64],
+            hdr: false,
+        }
+    }
+
+    #[test]
+    fn should_give_maximum_error_for_black_and_white() {
+        let black = uniform(Color::new(0.0, 0.0, 0.0));
+        let white = uniform(Color::new(1.0, 1.0, 1.0));
+        let errors = ldr_flip(&black, &white, DEFAULT_PIXELS_PER_DEGREE);
+        assert!(errors.iter().all(|e| (*e - 1.0).abs() < 0.05));
+    }
+
+    #[test]
+    fn should_grow_with_difference() {
+        let grey = uniform(Color::new(0.5, 0.5, 0.5));
+        let near = uniform(Color::new(0.52, 0.5, 0.5));
+        let far = uniform(Color::new(0.9, 0.5, 0.5));
+        let small = ldr_flip(&grey, &near, DEFAULT_PIXELS_PER_DEGREE)[0];
+        let large = ldr_flip(&grey, &far, DEFAULT_PIXELS_PER_DEGREE)[0];
+        assert!(small < large);
+    }
+}
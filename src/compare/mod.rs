@@ -0,0 +1,389 @@
+pub mod flip;
+pub mod ssim;
+
+use clap::ValueEnum;
+use image::DynamicImage;
+
+use crate::{
+    error::{Error, Result},
+    utils::{color::Color, is_hdr},
+};
+
+/// Image loaded for comparison, LDR images keep their encoded values in [0, 1] and
+/// HDR images (EXR, Radiance HDR) their linear values.
+#[derive(Clone)]
+pub struct FloatImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+    pub hdr: bool,
+}
+
+impl FloatImage {
+    pub fn load(path: &str) -> Result<Self> {
+        let image = image::open(path)?;
+        let hdr = matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        let rgb = image.to_rgb32f();
+
+        Ok(FloatImage {
+            width: rgb.width() as usize,
+            height: rgb.height() as usize,
+            pixels: rgb
+                .pixels()
+                .map(|p| Color::new(p[0] as f64, p[1] as f64, p[2] as f64))
+                .collect(),
+            hdr,
+        })
+    }
+
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> FloatImage {
+        FloatImage {
+            pixels: self
+                .pixels
+                .iter()
+                .map(|c| Color::new(f(c.r), f(c.g), f(c.b)))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    // Linear values, LDR images are assumed to be sRGB encoded
+    pub fn linear(&self) -> FloatImage {
+        if self.hdr {
+            self.clone()
+        } else {
+            self.map(srgb_to_linear)
+        }
+    }
+
+    // Encoded values clamped to [0, 1]
+    pub fn display(&self) -> FloatImage {
+        if self.hdr {
+            self.map(|x| linear_to_srgb(x.clamp(0.0, 1.0)))
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Linear values brightened by `stops`, halved for every stop below zero.
+    pub fn exposed(&self, stops: f64) -> FloatImage {
+        let scale = stops.exp2();
+        FloatImage {
+            hdr: true,
+            ..self.linear().map(|x| x * scale)
+        }
+    }
+
+    /// Saves the image, the format is guessed from the extension. HDR formats get the
+    /// linear values, the others the encoded values.
+    pub fn save(&self, file_path: &str) -> Result<()> {
+        let (width, height) = (self.width as u32, self.height as u32);
+        if is_hdr(file_path) {
+            let linear = self.linear();
+            image::Rgb32FImage::from_fn(width, height, |x, y| {
+                let c = linear.pixels[(y * width + x) as usize];
+                image::Rgb([c.r as f32, c.g as f32, c.b as f32])
+            })
+            .save(file_path)?;
+        } else {
+            let display = self.display();
+            image::RgbImage::from_fn(width, height, |x, y| {
+                let c = display.pixels[(y * width + x) as usize];
+                let encode = |x: f64| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+                image::Rgb([encode(c.r), encode(c.g), encode(c.b)])
+            })
+            .save(file_path)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn srgb_to_linear(x: f64) -> f64 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(x: f64) -> f64 {
+    if x <= 0.0031308 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn luminance(c: &Color) -> f64 {
+    0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b
+}
+
+// Convolves a single channel image with the separable kernel `horizontal` x `vertical`,
+// both of odd length and centered, clamping lookups to the image edges
+pub fn convolve_separable(
+    channel: &[f64],
+    width: usize,
+    height: usize,
+    horizontal: &[f64],
+    vertical: &[f64],
+) -> Vec<f64> {
+    let convolve = |input: &[f64], kernel: &[f64], horizontal: bool| -> Vec<f64> {
+        let radius = (kernel.len() / 2) as isize;
+        let mut output = vec![0.0; input.len()];
+
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let mut sum = 0.0;
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (sx, sy) = if horizontal {
+                        ((x + offset).clamp(0, width as isize - 1), y)
+                    } else {
+                        (x, (y + offset).clamp(0, height as isize - 1))
+                    };
+                    sum += weight * input[sy as usize * width + sx as usize];
+                }
+                output[y as usize * width + x as usize] = sum;
+            }
+        }
+        output
+    };
+
+    convolve(&convolve(channel, horizontal, true), vertical, false)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Metric {
+    Mse,
+    RelMse,
+    Psnr,
+    Ssim,
+    Flip,
+}
+
+impl Metric {
+    // PSNR and SSIM grow with similarity, the other metrics with the error
+    pub fn higher_is_better(&self) -> bool {
+        matches!(self, Metric::Psnr | Metric::Ssim)
+    }
+}
+
+pub struct Comparison {
+    pub mse: f64,
+    pub rel_mse: f64,
+    pub psnr: f64,
+    pub ssim: f64,
+    pub flip: f64,
+    squared_error_map: Vec<f64>,
+    rel_squared_error_map: Vec<f64>,
+    ssim_map: Vec<f64>,
+    flip_map: Vec<f64>,
+}
+
+impl Comparison {
+    /// Whether `metric` is at least as good as `threshold`.
+    pub fn within(&self, metric: Metric, threshold: f64) -> bool {
+        if metric.higher_is_better() {
+            self.value(metric) >= threshold
+        } else {
+            self.value(metric) <= threshold
+        }
+    }
+
+    pub fn value(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::Mse => self.mse,
+            Metric::RelMse => self.rel_mse,
+            Metric::Psnr => self.psnr,
+            Metric::Ssim => self.ssim,
+            Metric::Flip => self.flip,
+        }
+    }
+
+    // Per pixel error of `metric` normalised to [0, 1]
+    pub fn error_map(&self, metric: Metric) -> Vec<f64> {
+        let normalised = |map: &[f64]| {
+            let max = map.iter().cloned().fold(0.0, f64::max);
+            map.iter()
+                .map(|e| if max > 0.0 { e / max } else { 0.0 })
+                .collect()
+        };
+
+        match metric {
+            Metric::Mse | Metric::Psnr => normalised(&self.squared_error_map),
+            Metric::RelMse => normalised(&self.rel_squared_error_map),
+            Metric::Ssim => self
+                .ssim_map
+                .iter()
+                .map(|s| (1.0 - s).clamp(0.0, 1.0))
+                .collect(),
+            Metric::Flip => self.flip_map.clone(),
+        }
+    }
+}
+
+/// Compares `test` against `reference`.
+///
+/// When both images are LDR every metric works on the encoded values. As soon as one of
+/// them is HDR, MSE, relMSE and PSNR use linear values (PSNR relative to the peak of
+/// the reference), SSIM the clamped sRGB encoded values and FLIP switches to HDR-FLIP.
+pub fn compare(reference: &FloatImage, test: &FloatImage) -> Result<Comparison> {
+    if reference.width != test.width || reference.height != test.height {
+        return Err(Error::ImageSizeMismatch {
+            expected: (reference.width, reference.height),
+            found: (test.width, test.height),
+        });
+    }
+
+    let hdr = reference.hdr || test.hdr;
+    let (reference_values, test_values) = if hdr {
+        (reference.linear(), test.linear())
+    } else {
+        (reference.clone(), test.clone())
+    };
+
+    let mut squared_error_map = Vec::with_capacity(reference.pixels.len());
+    let mut rel_squared_error_map = Vec::with_capacity(reference.pixels.len());
+
+    for (r, t) in reference_values.pixels.iter().zip(&test_values.pixels) {
+        let mut squared_error = 0.0;
+        let mut rel_squared_error = 0.0;
+        for (a, b) in [(r.r, t.r), (r.g, t.g), (r.b, t.b)] {
+            squared_error += (a - b) * (a - b) / 3.0;
+            rel_squared_error += (a - b) * (a - b) / (a * a + 0.01) / 3.0;
+        }
+        squared_error_map.push(squared_error);
+        rel_squared_error_map.push(rel_squared_error);
+    }
+
+    let pixels = squared_error_map.len() as f64;
+    let mse = squared_error_map.iter().sum::<f64>() / pixels;
+    let rel_mse = rel_squared_error_map.iter().sum::<f64>() / pixels;
+
+    let peak = if hdr {
+        reference_values
+            .pixels
+            .iter()
+            .map(|c| c.r.max(c.g).max(c.b))
+            .fold(1.0, f64::max)
+    } else {
+        1.0
+    };
+    let psnr = 10.0 * (peak * peak / mse).log10();
+
+    let ssim_map = ssim::ssim_map(&reference.display(), &test.display());
+    let ssim = ssim_map.iter().sum::<f64>() / pixels;
+
+    let flip_map = if hdr {
+        flip::hdr_flip(
+            &reference_values,
+            &test_values,
+            flip::DEFAULT_PIXELS_PER_DEGREE,
+        )
+    } else {
+        flip::ldr_flip(
+            &reference.linear(),
+            &test.linear(),
+            flip::DEFAULT_PIXELS_PER_DEGREE,
+        )
+    };
+    let flip = flip_map.iter().sum::<f64>() / pixels;
+
+    Ok(Comparison {
+        mse,
+        rel_mse,
+        psnr,
+        ssim,
+        flip,
+        squared_error_map,
+        rel_squared_error_map,
+        ssim_map,
+        flip_map,
+    })
+}
+
+/// Writes `errors`, values in [0, 1], as a magma false-colour image.
+pub fn save_error_map(errors: &[f64], width: usize, height: usize, file_path: &str) -> Result<()> {
+    let mut imgbuf = image::RgbImage::new(width as u32, height as u32);
+
+    for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
+        let c = flip::magma(errors[y as usize * width + x as usize]);
+        *pixel = image::Rgb([
+            (c.r * 255.0).round() as u8,
+            (c.g * 255.0).round() as u8,
+            (c.b * 255.0).round() as u8,
+        ]);
+    }
+
+    imgbuf.save(file_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, FloatImage};
+    use crate::utils::color::Color;
+
+    fn image(pixels: Vec<Color>, hdr: bool) -> FloatImage {
+        FloatImage {
+            width: 4,
+            height: pixels.len() / 4,
+            pixels,
+            hdr,
+        }
+    }
+
+    #[test]
+    fn should_report_no_error_for_identical_images() {
+        let a = image(
+            (0..64)
+                .map(|i| Color::new(i as f64 / 64.0, 0.5, 1.0 - i as f64 / 64.0))
+                .collect(),
+            false,
+        );
+        let comparison = compare(&a, &a).unwrap();
+        assert_eq!(comparison.mse, 0.0);
+        assert_eq!(comparison.rel_mse, 0.0);
+        assert_eq!(comparison.psnr, f64::INFINITY);
+        assert!((comparison.ssim - 1.0).abs() < 1e-9);
+        assert!(comparison.flip < 1e-9);
+    }
+
+    #[test]
+    fn should_compute_mse_and_psnr() {
+        let a = image(vec![Color::new(0.5, 0.5, 0.5); 16], false);
+        let b = image(vec![Color::new(0.6, 0.6, 0.6); 16], false);
+        let comparison = compare(&a, &b).unwrap();
+        assert!((comparison.mse - 0.01).abs() < 1e-12);
+        assert!((comparison.psnr - 20.0).abs() < 1e-9);
+        assert!(comparison.flip > 0.0);
+    }
+
+    #[test]
+    fn should_compare_hdr_with_ldr_images() {
+        let hdr = image(vec![Color::new(4.0, 2.0, 0.5); 16], true);
+        let ldr = image(vec![Color::new(1.0, 1.0, 0.5); 16], false);
+        let comparison = compare(&hdr, &ldr).unwrap();
+        assert!(comparison.mse > 0.0);
+        assert!(comparison.flip > 0.0 && comparison.flip <= 1.0);
+    }
+
+    #[test]
+    fn should_reject_different_sizes() {
+        let a = image(vec![Color::default(); 16], false);
+        let b = image(vec![Color::default(); 8], true);
+        assert!(compare(&a, &b).is_err());
+    }
+
+    #[test]
+    fn should_expose_linear_values() {
+        let ldr = image(vec![Color::new(1.0, 0.0, 0.5); 16], false);
+        let exposed = ldr.exposed(1.0);
+        assert!(exposed.hdr);
+        assert_eq!(exposed.pixels[0].r, 2.0);
+        assert!((exposed.pixels[0].b - 2.0 * super::srgb_to_linear(0.5)).abs() < 1e-12);
+    }
+}
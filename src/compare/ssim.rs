@@ -0,0 +1,41 @@
+use crate::compare::{convolve_separable, luminance, FloatImage};
+
+const WINDOW_SIGMA: f64 = 1.5;
+const WINDOW_RADIUS: isize = 5;
+const C1: f64 = 0.01 * 0.01;
+const C2: f64 = 0.03 * 0.03;
+
+/// Per pixel structural similarity of the luminance of two images with values in
+/// [0, 1], using the usual 11x11 Gaussian window.
+pub fn ssim_map(reference: &FloatImage, test: &FloatImage) -> Vec<f64> {
+    let (width, height) = (reference.width, reference.height);
+
+    let window: Vec<f64> = (-WINDOW_RADIUS..=WINDOW_RADIUS)
+        .map(|x| f64::exp(-((x * x) as f64) / (2.0 * WINDOW_SIGMA * WINDOW_SIGMA)))
+        .collect();
+    let total: f64 = window.iter().sum();
+    let window: Vec<f64> = window.iter().map(|w| w / total).collect();
+    let blur = |channel: &[f64]| convolve_separable(channel, width, height, &window, &window);
+
+    let x: Vec<f64> = reference.pixels.iter().map(luminance).collect();
+    let y: Vec<f64> = test.pixels.iter().map(luminance).collect();
+    let product =
+        |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b).map(|(a, b)| a * b).collect() };
+
+    let mu_x = blur(&x);
+    let mu_y = blur(&y);
+    let xx = blur(&product(&x, &x));
+    let yy = blur(&product(&y, &y));
+    let xy = blur(&product(&x, &y));
+
+    (0..x.len())
+        .map(|i| {
+            let sigma_x = xx[i] - mu_x[i] * mu_x[i];
+            let sigma_y = yy[i] - mu_y[i] * mu_y[i];
+            let sigma_xy = xy[i] - mu_x[i] * mu_y[i];
+
+            ((2.0 * mu_x[i] * mu_y[i] + C1) * (2.0 * sigma_xy + C2))
+                / ((mu_x[i] * mu_x[i] + mu_y[i] * mu_y[i] + C1) * (sigma_x + sigma_y + C2))
+        })
+        .collect()
+}
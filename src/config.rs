@@ -1,10 +1,99 @@
+use crate::{
+    debug::RenderMode,
+    error::{Error, Result},
+    film::Filter,
+    integrator::IntegratorKind,
+    path::PathDepths,
+    sampler::SamplerKind,
+};
+
+/// Window of an image in pixels, from the top left corner, `x1` and `y1` excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Region {
+    pub fn new(x0: usize, y0: usize, x1: usize, y1: usize) -> Self {
+        Region { x0, y0, x1, y1 }
+    }
+
+    /// Region from fractions of the image size, rounded outwards to whole pixels.
+    pub fn normalised(x0: f64, y0: f64, x1: f64, y1: f64, width: usize, height: usize) -> Self {
+        let x = |f: f64| f.clamp(0.0, 1.0) * width as f64;
+        let y = |f: f64| f.clamp(0.0, 1.0) * height as f64;
+        Region {
+            x0: x(x0).floor() as usize,
+            y0: y(y0).floor() as usize,
+            x1: x(x1).ceil() as usize,
+            y1: y(y1).ceil() as usize,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.x1.saturating_sub(self.x0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1.saturating_sub(self.y0)
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// Whether the region holds the pixel `(i, j)`, `j` counting rows from the bottom.
+    pub fn contains(&self, (i, j): (usize, usize), image_height: usize) -> bool {
+        let y = image_height - 1 - j;
+        (self.x0..self.x1).contains(&i) && (self.y0..self.y1).contains(&y)
+    }
+
+    /// Image coordinates `(i, j)` of the `k`th pixel of the region, `j` counting rows from
+    /// the bottom like the rendered frames. Pixels go left to right, bottom row first.
+    pub fn pixel(&self, k: usize, image_height: usize) -> (usize, usize) {
+        let bottom = image_height - self.y1;
+        (self.x0 + k % self.width(), bottom + k / self.width())
+    }
+
+    /// Pixels of the region cut out of a frame stored bottom row first.
+    pub fn crop<T: Copy>(&self, frame: &[T], image_width: usize, image_height: usize) -> Vec<T> {
+        (0..self.area())
+            .map(|k| {
+                let (i, j) = self.pixel(k, image_height);
+                frame[j * image_width + i]
+            })
+            .collect()
+    }
+}
+
+/// Image and sampling settings of a render.
 #[derive(Clone)]
 pub struct Config {
     pub aspect_ratio: f64,
     pub image_width: usize,
     pub image_height: usize,
     pub samples_per_pixel: i32,
-    pub max_depth: usize,
+    /// Depth limits and Russian roulette of the paths.
+    pub depths: PathDepths,
+    pub sampler: SamplerKind,
+    pub seed: u64,
+    /// Only this window is rendered, the rest of the frame stays black.
+    pub region: Option<Region>,
+    /// Output only the region instead of the full frame.
+    pub crop: bool,
+    /// Collect the AOVs of the first hits along with the beauty.
+    pub aovs: bool,
+    /// Reconstruction filter splatting the samples to the pixels around them.
+    pub filter: Filter,
+    /// Path tracing or one of the debug views.
+    pub mode: RenderMode,
+    /// Integrator computing the light of the camera rays, when not showing a debug view.
+    pub integrator: IntegratorKind,
+    /// Reach of the ambient occlusion rays, a tenth of the size of the world when `None`.
+    pub ao_distance: Option<f64>,
 }
 
 impl Config {
@@ -13,6 +102,8 @@ impl Config {
         image_width: usize,
         samples_per_pixel: i32,
         max_depth: usize,
+        sampler: SamplerKind,
+        seed: u64,
     ) -> Self {
         let image_height = (image_width as f64 / aspect_ratio) as usize;
         Config {
@@ -20,7 +111,173 @@ impl Config {
             image_width,
             image_height,
             samples_per_pixel,
-            max_depth,
+            depths: PathDepths::new(max_depth),
+            sampler,
+            seed,
+            region: None,
+            crop: false,
+            aovs: false,
+            filter: Filter::default(),
+            mode: RenderMode::Path,
+            integrator: IntegratorKind::Path,
+            ao_distance: None,
         }
     }
+
+    /// Restricts the render to `region`, pixels keep their seeds so the window matches
+    /// the same window of a full render.
+    pub fn with_region(mut self, region: Region, crop: bool) -> Self {
+        self.region = Some(region);
+        self.crop = crop;
+        self
+    }
+
+    /// Collects the AOVs, see `Renderer::aovs`.
+    pub fn with_aovs(mut self) -> Self {
+        self.aovs = true;
+        self
+    }
+
+    pub fn with_depths(mut self, depths: PathDepths) -> Self {
+        self.depths = depths;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: RenderMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_integrator(mut self, integrator: IntegratorKind, ao_distance: Option<f64>) -> Self {
+        self.integrator = integrator;
+        self.ao_distance = ao_distance;
+        self
+    }
+
+    /// Window actually rendered, the whole image without a region.
+    pub fn window(&self) -> Region {
+        self.region
+            .unwrap_or_else(|| Region::new(0, 0, self.image_width, self.image_height))
+    }
+
+    /// Pixels whose samples reach the window through the filter, the window grown by the
+    /// filter reach and clipped to the image.
+    pub fn sample_window(&self) -> Region {
+        let window = self.window();
+        if self.region.is_none() {
+            return window;
+        }
+        let reach = self.filter.reach();
+        Region::new(
+            window.x0.saturating_sub(reach),
+            window.y0.saturating_sub(reach),
+            usize::min(window.x1 + reach, self.image_width),
+            usize::min(window.y1 + reach, self.image_height),
+        )
+    }
+
+    /// Size of the saved image.
+    pub fn output_size(&self) -> (usize, usize) {
+        match self.region {
+            Some(region) if self.crop => (region.width(), region.height()),
+            _ => (self.image_width, self.image_height),
+        }
+    }
+
+    /// Checks that the settings can produce an image.
+    pub fn validate(&self) -> Result<()> {
+        if self.image_width < 2 || self.image_height < 2 {
+            return Err(Error::InvalidConfig(format!(
+                "image must be at least 2x2 pixels, got {}x{}",
+                self.image_width, self.image_height
+            )));
+        }
+        if self.samples_per_pixel <= 0 {
+            return Err(Error::InvalidConfig(format!(
+                "samples per pixel must be positive, got {}",
+                self.samples_per_pixel
+            )));
+        }
+        if self.filter.radius.is_nan() || self.filter.radius <= 0.0 {
+            return Err(Error::InvalidConfig(format!(
+                "filter radius must be positive, got {}",
+                self.filter.radius
+            )));
+        }
+        if let Some(distance) = self.ao_distance.filter(|d| d.is_nan() || *d <= 0.0) {
+            return Err(Error::InvalidConfig(format!(
+                "ambient occlusion distance must be positive, got {}",
+                distance
+            )));
+        }
+        if self.aovs && self.mode != RenderMode::Path {
+            return Err(Error::InvalidConfig(String::from(
+                "AOVs are only collected when path tracing",
+            )));
+        }
+        if let Some(region) = self.region {
+            if region.area() == 0 || region.x1 > self.image_width || region.y1 > self.image_height {
+                return Err(Error::InvalidConfig(format!(
+                    "region {},{},{},{} is empty or outside of the {}x{} image",
+                    region.x0, region.y0, region.x1, region.y1, self.image_width, self.image_height
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Region};
+    use crate::{debug::RenderMode, sampler::SamplerKind};
+
+    #[test]
+    fn should_reject_empty_image() {
+        let config = Config::new(16.0 / 9.0, 1, 16, 10, SamplerKind::Independent, 0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn should_reject_no_samples() {
+        let config = Config::new(1.0, 100, 0, 10, SamplerKind::Independent, 0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn should_reject_region_outside_image() {
+        let config = Config::new(1.0, 100, 16, 10, SamplerKind::Independent, 0);
+        assert!(config
+            .clone()
+            .with_region(Region::new(10, 10, 101, 20), false)
+            .validate()
+            .is_err());
+        assert!(config
+            .clone()
+            .with_region(Region::new(10, 10, 10, 20), false)
+            .validate()
+            .is_err());
+        assert!(config
+            .with_region(Region::new(0, 0, 100, 100), true)
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn should_reject_aovs_of_debug_views() {
+        let config = Config::new(1.0, 100, 16, 10, SamplerKind::Independent, 0).with_aovs();
+        assert!(config.clone().validate().is_ok());
+        assert!(config.with_mode(RenderMode::Cost).validate().is_err());
+    }
+
+    #[test]
+    fn should_round_normalised_region_outwards() {
+        let region = Region::normalised(0.25, 0.1, 0.5, 0.55, 10, 10);
+        assert_eq!(region, Region::new(2, 1, 5, 6));
+    }
 }
@@ -0,0 +1,174 @@
+use clap::ValueEnum;
+
+use crate::{
+    camera::Camera,
+    compare::flip::magma,
+    hitable::Hitable,
+    integrator::path::PathIntegrator,
+    material::ScatterRay,
+    path::{Bounce, PathDepths},
+    ray::Ray,
+    sampler::Sampler,
+    stats,
+    utils::{color::Color, INFINITY},
+    vec3::Vec3,
+};
+
+// Traversal cost shown at the top of the colour map, on a log scale
+const COST_SCALE: f64 = 1024.0;
+// Bounce count shown at the top of the colour map
+const BOUNCE_SCALE: usize = 16;
+// Barycentric distance to the nearest edge drawn as a wire
+const WIRE_WIDTH: f64 = 0.03;
+
+/// What the renderer computes for every camera ray: the path traced image or one of the
+/// debug views, which show their values as they are without gamma correction.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Path traced image
+    #[default]
+    Path,
+    /// Normal used for shading, facing the ray, mapped from [-1, 1]
+    ShadingNormal,
+    /// Outward normal of the surface, mapped from [-1, 1]
+    GeometricNormal,
+    /// Texture coordinates in red and green
+    Uv,
+    /// Distance along the camera view axis, white at the camera and mid grey at the focus
+    /// distance
+    Depth,
+    /// Colour the first hit filters light by, lights show their emission
+    Albedo,
+    /// BVH nodes and primitives tested by the camera ray, on a log scale up to 1024
+    Cost,
+    /// Bounces of the path until it escapes, is absorbed, reaches a depth limit or is
+    /// ended by Russian roulette, up to 16
+    Bounces,
+    /// Triangle edges over a grey shading, for meshes
+    Wireframe,
+}
+
+impl RenderMode {
+    /// Colour of the camera ray `r` in a debug mode, each channel in [0, 1].
+    pub fn shade(
+        &self,
+        r: &Ray,
+        background: &Color,
+        world: &dyn Hitable,
+        depths: &PathDepths,
+        sampler: &mut dyn Sampler,
+        cam: &Camera,
+    ) -> Color {
+        let black = Color::new(0.0, 0.0, 0.0);
+        if depths.max == 0 {
+            return black;
+        }
+
+        let hit = |r: &Ray| world.hit(r, 0.001, INFINITY);
+        match self {
+            RenderMode::Path => black,
+            RenderMode::ShadingNormal => hit(r).map_or(black, |rec| unit_color(rec.normal)),
+            RenderMode::GeometricNormal => hit(r).map_or(black, |rec| {
+                unit_color(if rec.front_face {
+                    rec.normal
+                } else {
+                    -rec.normal
+                })
+            }),
+            RenderMode::Uv => hit(r).map_or(black, |rec| {
+                Color::new(rec.u.clamp(0.0, 1.0), rec.v.clamp(0.0, 1.0), 0.0)
+            }),
+            RenderMode::Depth => hit(r).map_or(black, |rec| {
+                let (_, _, w) = cam.basis();
+                let depth = Vec3::dot(&(rec.p - cam.origin()), &-w).max(0.0);
+                let value = cam.focus_dist() / (cam.focus_dist() + depth);
+                Color::new(value, value, value)
+            }),
+            RenderMode::Albedo => hit(r).map_or(*background, |rec| {
+                match rec.mat_ptr.scatter(r, &rec, sampler) {
+                    Some(ScatterRay { attenuation, .. }) => attenuation,
+                    None => rec.mat_ptr.emitted(rec.u, rec.v, &rec.p),
+                }
+            }),
+            RenderMode::Cost => {
+                let before = stats::current().traversal();
+                hit(r);
+                let cost = (stats::current().traversal() - before) as f64;
+                magma(f64::ln_1p(cost) / f64::ln_1p(COST_SCALE))
+            }
+            RenderMode::Bounces => {
+                let mut bounces = 0;
+                PathIntegrator::new(*depths).trace(r, background, world, sampler, &mut |vertex| {
+                    if let Some(Bounce::Continue { .. }) = vertex.bounce {
+                        bounces += 1;
+                    }
+                });
+                magma(bounces as f64 / usize::min(depths.max, BOUNCE_SCALE) as f64)
+            }
+            RenderMode::Wireframe => hit(r).map_or(black, |rec| match rec.barycentric {
+                Some((u, v)) if f64::min(f64::min(u, v), 1.0 - u - v) < WIRE_WIDTH => {
+                    Color::new(1.0, 0.6, 0.1)
+                }
+                _ => {
+                    let facing = Vec3::dot(&rec.normal, &Vec3::unit_vector(&r.direction())).abs();
+                    let grey = 0.15 + 0.7 * facing;
+                    Color::new(grey, grey, grey)
+                }
+            }),
+        }
+    }
+}
+
+fn unit_color(n: Vec3) -> Color {
+    Color::from(n) * 0.5 + Color::new(0.5, 0.5, 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderMode;
+    use crate::{
+        camera::Camera, hitable::triangle::Triangle, hitable::Hitable,
+        material::lambertian::Lambertian, path::PathDepths, ray::Ray, sampler::SamplerKind,
+        vec3::Vec3, Color,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn should_draw_triangle_edges_only_near_them() {
+        let triangle = Triangle::new(
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(0.0, 1.0, -1.0),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let world: &dyn Hitable = &triangle;
+        let cam = Camera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            90.0,
+            1.0,
+            0.0,
+            1.0,
+            None,
+        );
+        let mut sampler = SamplerKind::Independent.build(1, 0);
+        let depths = PathDepths::new(8);
+        let mut shade = |target: Vec3| {
+            let r = Ray::new(Vec3::new(0.0, 0.0, 0.0), target);
+            RenderMode::Wireframe.shade(
+                &r,
+                &Color::default(),
+                world,
+                &depths,
+                sampler.as_mut(),
+                &cam,
+            )
+        };
+
+        let edge = shade(Vec3::new(0.0, -0.99, -1.0));
+        let inside = shade(Vec3::new(0.0, -0.2, -1.0));
+        assert_eq!(edge, Color::new(1.0, 0.6, 0.1));
+        assert_eq!(inside.r, inside.b);
+    }
+}
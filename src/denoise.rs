@@ -0,0 +1,251 @@
+use crate::{aov::AovBuffers, compare::luminance, utils::color::Color, vec3::Vec3};
+
+// B3 spline, the à-trous kernel is its outer product spread by the step size
+const KERNEL: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al. 2010) guided by the albedo,
+/// normal and depth AOVs, with lighting differences judged against the variance of the
+/// samples like in SVGF (Schied et al. 2017).
+///
+/// The lighting is filtered apart from the albedo and the emission of the first hit,
+/// which are noise free, so textures and lights stay sharp.
+#[derive(Debug, Clone, Copy)]
+pub struct Denoiser {
+    /// Number of passes, each one doubles the reach of the filter.
+    pub iterations: usize,
+    /// Tolerance on the lighting difference, in standard deviations of the pixel.
+    pub sigma_luminance: f64,
+    pub sigma_normal: f64,
+    /// Tolerance on the relative depth difference.
+    pub sigma_depth: f64,
+    pub sigma_albedo: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser {
+            iterations: 5,
+            sigma_luminance: 4.0,
+            sigma_normal: 0.3,
+            sigma_depth: 0.1,
+            sigma_albedo: 0.1,
+        }
+    }
+}
+
+// Guide features of a pixel
+struct Feature {
+    albedo: Color,
+    normal: Vec3,
+    depth: f64,
+}
+
+impl Denoiser {
+    pub fn new(iterations: usize) -> Self {
+        Denoiser {
+            iterations,
+            ..Default::default()
+        }
+    }
+
+    /// Linear denoised beauty of the AOVs, rows bottom first.
+    pub fn denoise(&self, aovs: &AovBuffers) -> Vec<Color> {
+        let features: Vec<Feature> = aovs
+            .pixels
+            .iter()
+            .map(|p| Feature {
+                albedo: p.albedo,
+                normal: p.normal,
+                depth: p.depth,
+            })
+            .collect();
+
+        // Light arriving at the first hit, without its albedo and emission
+        let mut lighting: Vec<(Color, f64)> = aovs
+            .pixels
+            .iter()
+            .map(|p| {
+                let reflected = p.direct + p.indirect;
+                let light = Color::new(
+                    demodulate(reflected.r, p.albedo.r),
+                    demodulate(reflected.g, p.albedo.g),
+                    demodulate(reflected.b, p.albedo.b),
+                );
+                let albedo = luminance(&p.albedo);
+                (light, demodulate(p.variance, albedo * albedo))
+            })
+            .collect();
+
+        for iteration in 0..self.iterations {
+            lighting = self.pass(&lighting, &features, aovs.width, aovs.height, iteration);
+        }
+
+        lighting
+            .iter()
+            .zip(&aovs.pixels)
+            .map(|((l, _), p)| *l * p.albedo + p.emission)
+            .collect()
+    }
+
+    // One filtering pass over the lighting and its variance
+    fn pass(
+        &self,
+        lighting: &[(Color, f64)],
+        features: &[Feature],
+        width: usize,
+        height: usize,
+        iteration: usize,
+    ) -> Vec<(Color, f64)> {
+        let step = 1_isize << iteration;
+        let mut output = Vec::with_capacity(lighting.len());
+
+        // Variance estimates of a few samples are noisy themselves, smooth them first
+        let blurred_variance = blur(
+            &lighting.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+            width,
+            height,
+        );
+
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let p = (y as usize) * width + x as usize;
+                let fp = &features[p];
+                let lp = lighting[p].0;
+                let tolerance = self.sigma_luminance * blurred_variance[p].sqrt() + 1e-6;
+
+                let mut sum = Color::new(0.0, 0.0, 0.0);
+                let mut sum_variance = 0.0;
+                let mut total = 0.0;
+                for (dy, ky) in KERNEL.iter().enumerate() {
+                    for (dx, kx) in KERNEL.iter().enumerate() {
+                        let qx = x + (dx as isize - 2) * step;
+                        let qy = y + (dy as isize - 2) * step;
+                        if qx < 0 || qy < 0 || qx >= width as isize || qy >= height as isize {
+                            continue;
+                        }
+                        let q = (qy as usize) * width + qx as usize;
+                        let fq = &features[q];
+                        let (lq, variance_q) = lighting[q];
+
+                        let light = (luminance(&lp) - luminance(&lq)).abs() / tolerance;
+                        let normal =
+                            (fp.normal - fq.normal).length_squared() / self.sigma_normal.powi(2);
+                        let depth = (fp.depth - fq.depth).abs()
+                            / (self.sigma_depth * f64::max(fp.depth.abs(), fq.depth.abs()) + 1e-6);
+                        let albedo =
+                            distance_squared(fp.albedo, fq.albedo) / self.sigma_albedo.powi(2);
+
+                        let weight = kx * ky * f64::exp(-(light + normal + depth + albedo));
+                        sum = sum + lq * weight;
+                        sum_variance += weight * weight * variance_q;
+                        total += weight;
+                    }
+                }
+                // The centre pixel always has a positive weight
+                output.push((sum * (1.0 / total), sum_variance / (total * total)));
+            }
+        }
+        output
+    }
+}
+
+// Blur with the B3 kernel, which covers a 5x5 window
+fn blur(values: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let mut output = Vec::with_capacity(values.len());
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let mut sum = 0.0;
+            let mut total = 0.0;
+            for (dy, ky) in KERNEL.iter().enumerate() {
+                for (dx, kx) in KERNEL.iter().enumerate() {
+                    let (qx, qy) = (x + dx as isize - 2, y + dy as isize - 2);
+                    if qx >= 0 && qy >= 0 && qx < width as isize && qy < height as isize {
+                        sum += kx * ky * values[qy as usize * width + qx as usize];
+                        total += kx * ky;
+                    }
+                }
+            }
+            output.push(sum / total);
+        }
+    }
+    output
+}
+
+fn demodulate(light: f64, albedo: f64) -> f64 {
+    if albedo > 1e-3 {
+        light / albedo
+    } else {
+        0.0
+    }
+}
+
+fn distance_squared(a: Color, b: Color) -> f64 {
+    (a.r - b.r).powi(2) + (a.g - b.g).powi(2) + (a.b - b.b).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Denoiser;
+    use crate::{
+        aov::{AovBuffers, AovSample},
+        utils::color::Color,
+        vec3::Vec3,
+    };
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    // Grey wall on the left, differently oriented wall on the right, both lit by noise
+    // around 0.5
+    fn noisy_walls(size: usize) -> AovBuffers {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let pixels = (0..size * size)
+            .map(|k| {
+                let left = k % size < size / 2;
+                let light = if left { 0.5 } else { 0.1 } * rng.gen_range(0.0..2.0);
+                AovSample {
+                    albedo: Color::new(1.0, 1.0, 1.0),
+                    normal: if left {
+                        Vec3::new(1.0, 0.0, 0.0)
+                    } else {
+                        Vec3::new(0.0, 0.0, 1.0)
+                    },
+                    depth: 2.0,
+                    direct: Color::new(light, light, light),
+                    // Variance of a uniform sample of [0, 2 * mean]
+                    variance: (if left { 0.5_f64 } else { 0.1 }).powi(2) / 3.0,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        AovBuffers::new(size, size, pixels)
+    }
+
+    fn variance(values: impl Iterator<Item = f64> + Clone) -> f64 {
+        let n = values.clone().count() as f64;
+        let mean = values.clone().sum::<f64>() / n;
+        values.map(|v| (v - mean).powi(2)).sum::<f64>() / n
+    }
+
+    #[test]
+    fn should_remove_noise_but_keep_edges() {
+        let size = 32;
+        let aovs = noisy_walls(size);
+        let denoised = Denoiser::default().denoise(&aovs);
+
+        let left = |pixels: &[Color]| {
+            (0..size * size)
+                .filter(|k| k % size < size / 2 - 1)
+                .map(|k| pixels[k].r)
+                .collect::<Vec<_>>()
+        };
+        let noisy: Vec<Color> = aovs.pixels.iter().map(|p| p.direct).collect();
+        let before = variance(left(&noisy).into_iter());
+        let after = variance(left(&denoised).into_iter());
+        assert!(after < before / 10.0, "{} -> {}", before, after);
+
+        // The dim wall does not bleed into the bright one
+        let mean = left(&denoised).iter().sum::<f64>() / left(&denoised).len() as f64;
+        assert!((mean - 0.5).abs() < 0.05, "{}", mean);
+        let edge = denoised[size / 2 - 1].r;
+        assert!(edge > 0.3, "{}", edge);
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+use image::ImageError;
+
+/// Errors reported by the scene loaders, the renderer and the image tools.
+#[derive(Debug)]
+pub enum Error {
+    /// No built-in scene with this index
+    SceneNotFound { index: usize, count: usize },
+    /// No built-in scene with this name
+    UnknownScene(String),
+    /// The OBJ file could not be read or parsed
+    ObjLoad {
+        path: String,
+        source: tobj::LoadError,
+    },
+    /// A scene was built without a camera
+    MissingCamera,
+    /// An object without a bounding box was put in a BVH
+    MissingBoundingBox,
+    /// A BVH was built over no objects
+    EmptyBvh,
+    /// The render settings can't produce an image
+    InvalidConfig(String),
+    /// Compared images have different sizes
+    ImageSizeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// Reading or writing an image failed
+    Image(ImageError),
+    /// Writing a multi-layer EXR failed
+    Exr(exr::error::Error),
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SceneNotFound { index, count } => write!(
+                f,
+                "Scene {} does not exist there are {} scenes",
+                index, count
+            ),
+            Error::UnknownScene(name) => write!(
+                f,
+                "Scene {} does not exist, `rtxon list-scenes` shows the built-in scenes",
+                name
+            ),
+            Error::ObjLoad { path, source } => write!(f, "Failed to load {}: {}", path, source),
+            Error::MissingCamera => write!(f, "The scene has no camera"),
+            Error::MissingBoundingBox => write!(f, "Object without bounding box in BVH"),
+            Error::EmptyBvh => write!(f, "Cannot build a BVH without objects"),
+            Error::InvalidConfig(reason) => write!(f, "Invalid render settings: {}", reason),
+            Error::ImageSizeMismatch { expected, found } => write!(
+                f,
+                "Image is {}x{} but expected {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            Error::Image(e) => write!(f, "{}", e),
+            Error::Exr(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ObjLoad { source, .. } => Some(source),
+            Error::Image(e) => Some(e),
+            Error::Exr(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(e: ImageError) -> Self {
+        Error::Image(e)
+    }
+}
+
+impl From<exr::error::Error> for Error {
+    fn from(e: exr::error::Error) -> Self {
+        Error::Exr(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,217 @@
+use clap::ValueEnum;
+
+use crate::{
+    utils::{color::Color, PI},
+    Config,
+};
+
+/// Shape of the reconstruction filter weighting the samples around every pixel.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    /// Every sample within the radius counts the same
+    Box,
+    /// Weight falling linearly to zero at the radius
+    Tent,
+    /// Gaussian with a standard deviation of a third of the radius
+    Gaussian,
+    /// Mitchell-Netravali cubic with B = C = 1/3, sharper with slight ringing
+    Mitchell,
+    /// Windowed sinc, sharpest with the most ringing
+    Lanczos,
+}
+
+impl FilterKind {
+    /// Radius in pixels used when none is given.
+    pub fn default_radius(&self) -> f64 {
+        match self {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian => 1.5,
+            FilterKind::Mitchell | FilterKind::Lanczos => 2.0,
+        }
+    }
+}
+
+/// Separable reconstruction filter, `radius` in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub radius: f64,
+}
+
+impl Default for Filter {
+    /// Box filter over the pixel, which averages the samples of every pixel.
+    fn default() -> Self {
+        Filter::new(FilterKind::Box, None)
+    }
+}
+
+impl Filter {
+    pub fn new(kind: FilterKind, radius: Option<f64>) -> Self {
+        Filter {
+            kind,
+            radius: radius.unwrap_or_else(|| kind.default_radius()),
+        }
+    }
+
+    /// Weight of a sample `(x, y)` pixels away from a pixel centre.
+    pub fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let r = self.radius;
+        let x = x.abs();
+        if x > r {
+            return 0.0;
+        }
+        match self.kind {
+            FilterKind::Box => 1.0,
+            FilterKind::Tent => r - x,
+            FilterKind::Gaussian => {
+                let sigma = r / 3.0;
+                let gaussian = |x: f64| f64::exp(-x * x / (2.0 * sigma * sigma));
+                gaussian(x) - gaussian(r)
+            }
+            FilterKind::Mitchell => mitchell(2.0 * x / r),
+            FilterKind::Lanczos => sinc(x) * sinc(x / r),
+        }
+    }
+
+    /// Pixels further than this from a sample get none of it.
+    pub fn reach(&self) -> usize {
+        self.radius.ceil() as usize
+    }
+}
+
+// Mitchell-Netravali cubic over [0, 2] with B = C = 1/3
+fn mitchell(x: f64) -> f64 {
+    let (b, c) = (1.0 / 3.0, 1.0 / 3.0);
+    let value = if x < 1.0 {
+        (12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+            + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+            + (6.0 - 2.0 * b)
+    } else {
+        (-b - 6.0 * c) * x.powi(3)
+            + (6.0 * b + 30.0 * c) * x.powi(2)
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c)
+    };
+    value / 6.0
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        f64::sin(PI * x) / (PI * x)
+    }
+}
+
+/// Weighted sums of the samples of one row of pixels, splatted on the band of rows the
+/// filter reaches. Frames add up the tiles of their rows in order, so the sums do not
+/// depend on how the rows were shared between threads.
+#[derive(Debug)]
+pub struct FilmTile {
+    filter: Filter,
+    image_width: usize,
+    // Row of the pixels whose samples the tile receives, and first row of the band, both
+    // counted from the bottom like the rendered frames
+    source_row: usize,
+    row: usize,
+    rows: usize,
+    sums: Vec<(Color, f64)>,
+}
+
+impl FilmTile {
+    /// Tile receiving the samples of the pixels in row `source_row`.
+    pub fn new(config: &Config, source_row: usize) -> Self {
+        let reach = config.filter.reach();
+        let row = source_row.saturating_sub(reach);
+        let end = usize::min(source_row + reach + 1, config.image_height);
+        FilmTile {
+            filter: config.filter,
+            image_width: config.image_width,
+            source_row,
+            row,
+            rows: end - row,
+            sums: vec![(Color::new(0.0, 0.0, 0.0), 0.0); (end - row) * config.image_width],
+        }
+    }
+
+    pub fn source_row(&self) -> usize {
+        self.source_row
+    }
+
+    /// Adds a sample at `(x, y)` on the film, in pixels from the bottom left corner, to
+    /// every pixel the filter reaches. A pixel gets the samples with offsets from its
+    /// centre in `(-radius, radius]`, so a box of radius 0.5 keeps samples to their pixel.
+    pub fn add_sample(&mut self, (x, y): (f64, f64), color: Color) {
+        let r = self.filter.radius;
+        let span = |c: f64, count: usize| {
+            let first = (c - 0.5 - r).floor() + 1.0;
+            let last = (c - 0.5 + r).floor();
+            (first.max(0.0) as usize, last.min(count as f64 - 1.0))
+        };
+
+        let (i0, i1) = span(x, self.image_width);
+        let (j0, j1) = span(y, self.row + self.rows);
+        if i1 < 0.0 || j1 < 0.0 {
+            return;
+        }
+        for j in usize::max(j0, self.row)..=j1 as usize {
+            let dy = j as f64 + 0.5 - y;
+            for i in i0..=i1 as usize {
+                let weight = self.filter.evaluate(i as f64 + 0.5 - x, dy);
+                if weight != 0.0 {
+                    let sum = &mut self.sums[(j - self.row) * self.image_width + i];
+                    sum.0 = sum.0 + color * weight;
+                    sum.1 += weight;
+                }
+            }
+        }
+    }
+
+    /// Adds the sums of the tile to the sums of a whole frame.
+    pub fn merge_into(&self, frame: &mut [(Color, f64)]) {
+        let start = self.row * self.image_width;
+        for (sum, tile) in frame[start..start + self.sums.len()]
+            .iter_mut()
+            .zip(&self.sums)
+        {
+            sum.0 = sum.0 + tile.0;
+            sum.1 += tile.1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, FilterKind};
+
+    #[test]
+    fn should_vanish_at_the_radius() {
+        for kind in [
+            FilterKind::Tent,
+            FilterKind::Gaussian,
+            FilterKind::Mitchell,
+            FilterKind::Lanczos,
+        ] {
+            let filter = Filter::new(kind, None);
+            assert!(filter.evaluate(0.0, 0.0) > 0.0);
+            assert!(
+                filter.evaluate(filter.radius, 0.0).abs() < 1e-9,
+                "{:?}",
+                kind
+            );
+            assert_eq!(filter.evaluate(filter.radius + 0.1, 0.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn should_have_negative_lobes_for_sharp_filters() {
+        assert!(Filter::new(FilterKind::Lanczos, None).evaluate(1.5, 0.0) < 0.0);
+        assert!(Filter::new(FilterKind::Mitchell, None).evaluate(1.5, 0.0) < 0.0);
+        assert!(Filter::new(FilterKind::Gaussian, None).evaluate(1.4, 0.0) >= 0.0);
+    }
+}
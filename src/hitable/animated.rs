@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    animation::TransformTrack,
+    hitable::{HitRecord, Hitable},
+    inspect::Inspector,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+// Times sampled between keyframes when bounding the motion
+const BOUND_SAMPLES: usize = 16;
+
+/// Any object moved by a keyframed transform, evaluated at the time of each ray.
+pub struct Animated {
+    pub object: Arc<dyn Hitable>,
+    pub transform: TransformTrack,
+}
+
+impl Animated {
+    pub fn new<T: Hitable + 'static>(object: T, transform: TransformTrack) -> Self {
+        Animated {
+            object: Arc::new(object),
+            transform,
+        }
+    }
+
+    fn corners(b: &Aabb) -> [Vec3; 8] {
+        let (lo, hi) = (b.minimum, b.maximum);
+        [
+            Vec3::new(lo.x, lo.y, lo.z),
+            Vec3::new(hi.x, lo.y, lo.z),
+            Vec3::new(lo.x, hi.y, lo.z),
+            Vec3::new(hi.x, hi.y, lo.z),
+            Vec3::new(lo.x, lo.y, hi.z),
+            Vec3::new(hi.x, lo.y, hi.z),
+            Vec3::new(lo.x, hi.y, hi.z),
+            Vec3::new(hi.x, hi.y, hi.z),
+        ]
+    }
+}
+
+impl Hitable for Animated {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Rays without a time see the object as it is at time 0
+        let transform = self.transform.sample(r.time.unwrap_or(0.0));
+
+        // Affine transforms keep the ray parameter, so t needs no conversion
+        let local = Ray::new_with_time(
+            transform.inverse_point(r.origin),
+            transform.inverse_vector(r.direction),
+            r.time,
+        );
+        let mut rec = self.object.hit(&local, t_min, t_max)?;
+        rec.p = transform.point(rec.p);
+        rec.normal = transform.normal(rec.normal);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
+        let local = self.object.bounding_box(time)?;
+        let corners = Self::corners(&local);
+
+        // Evenly spaced times plus every keyframe inside the interval
+        let mut times: Vec<f64> = (0..=BOUND_SAMPLES)
+            .map(|i| time.0 + (time.1 - time.0) * i as f64 / BOUND_SAMPLES as f64)
+            .chain(
+                self.transform
+                    .key_times()
+                    .into_iter()
+                    .filter(|t| *t > time.0 && *t < time.1),
+            )
+            .collect();
+        times.sort_by(f64::total_cmp);
+
+        let mut output: Option<Aabb> = None;
+        let mut previous: Option<[Vec3; 8]> = None;
+        for t in times {
+            let transform = self.transform.sample(t);
+            let moved = corners.map(|c| transform.point(c));
+
+            // Rotations bend the path of the corners between samples, pad by half of
+            // the longest step so the curve stays inside
+            let padding = previous.map_or(0.0, |previous| {
+                moved
+                    .iter()
+                    .zip(previous.iter())
+                    .map(|(a, b)| (*a - *b).length() / 2.0)
+                    .fold(0.0, f64::max)
+            });
+            previous = Some(moved);
+
+            for corner in moved {
+                let b = Aabb::new(
+                    corner - Vec3::from_one(padding),
+                    corner + Vec3::from_one(padding),
+                );
+                output = Some(match output {
+                    None => b,
+                    Some(output) => Aabb::surrounding_box(output, b),
+                });
+            }
+        }
+        output
+    }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.object(std::mem::size_of_val(self));
+        self.object.inspect(inspector);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::Animated;
+    use crate::{
+        animation::{Interpolation, Track, TransformTrack},
+        hitable::{sphere::Sphere, Hitable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        utils::color::Color,
+        vec3::Vec3,
+    };
+
+    fn moving_sphere() -> Animated {
+        let sphere = Sphere::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let translation = Track::new(0.0, Vec3::new(0.0, 0.0, -2.0), Interpolation::Linear).key(
+            1.0,
+            Vec3::new(4.0, 0.0, -2.0),
+            Interpolation::Linear,
+        );
+        Animated::new(
+            sphere,
+            TransformTrack::new(
+                translation,
+                Track::constant(Vec3::new(0.0, 0.0, 0.0)),
+                Track::constant(1.0),
+            ),
+        )
+    }
+
+    #[test]
+    fn should_hit_object_where_it_is_at_ray_time() {
+        let animated = moving_sphere();
+        let towards_start = Ray::new_with_time(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Some(0.0),
+        );
+        let rec = animated.hit(&towards_start, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.t - 1.5).abs() < 1e-9);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+
+        let later = Ray::new_with_time(towards_start.origin, towards_start.direction, Some(1.0));
+        assert!(animated.hit(&later, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn should_bound_the_whole_motion() {
+        let b = moving_sphere().bounding_box((0.0, 1.0)).unwrap();
+        assert!(b.minimum.x <= -0.5 && b.maximum.x >= 4.5);
+        let start = moving_sphere().bounding_box((0.0, 0.0)).unwrap();
+        assert!(start.maximum.x < 1.0);
+    }
+}
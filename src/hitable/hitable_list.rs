@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use crate::{
     aabb::Aabb,
-    hitable::{HitRecord, Hitable},
+    bhv::BHVNode,
+    hitable::{identified::Identified, HitRecord, Hitable},
+    inspect::Inspector,
     ray::Ray,
     vec3::Vec3,
 };
@@ -10,34 +12,93 @@ use crate::{
 #[derive(Clone)]
 pub struct HitableList {
     objects: Vec<Arc<dyn Hitable>>,
+    // Name and number of objects of the meshes added with `add_mesh`, for `rtxon info`
+    meshes: Vec<(Arc<str>, usize)>,
 }
 
 impl HitableList {
     pub fn new() -> Self {
         HitableList {
             objects: Vec::new(),
+            meshes: Vec::new(),
         }
     }
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.meshes.clear();
     }
 
     pub fn add<T: Hitable + 'static>(&mut self, hitable: T) {
         self.objects.push(Arc::new(hitable));
     }
+
+    /// Adds the triangles, or other objects, of the mesh called `name`.
+    pub fn add_mesh<T: Hitable + 'static>(
+        &mut self,
+        name: impl Into<Arc<str>>,
+        objects: impl IntoIterator<Item = T>,
+    ) {
+        let count = self.objects.len();
+        for object in objects {
+            self.add(object);
+        }
+        self.meshes.push((name.into(), self.objects.len() - count));
+    }
+
+    /// Adds an object that may be shared with other lists.
+    pub fn add_arc(&mut self, hitable: Arc<dyn Hitable>) {
+        self.objects.push(hitable);
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Same objects with the bounded ones gathered in a bounding volume hierarchy, built
+    /// with their bounds over the `time` interval so moving objects stay inside. Objects
+    /// are numbered from 1 in the order they were added, hits report that `object_id`.
+    pub fn into_bvh(self, time: (f64, f64)) -> HitableList {
+        let meshes = self.meshes;
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = self
+            .objects
+            .into_iter()
+            .enumerate()
+            .map(|(index, object)| -> Arc<dyn Hitable> {
+                Arc::new(Identified::new(index + 1, object))
+            })
+            .partition(|object| object.bounding_box(time).is_some());
+
+        let mut objects = unbounded;
+        match bounded.len() {
+            0 => {}
+            1 => objects.extend(bounded),
+            count => match BHVNode::new(bounded.clone(), (0, count), time) {
+                Ok(bvh) => objects.push(Arc::new(bvh)),
+                Err(_) => objects.extend(bounded),
+            },
+        }
+        HitableList { objects, meshes }
+    }
+}
+
+impl Default for HitableList {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Hitable for HitableList {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let mut temp_rec = HitRecord::default();
         let mut rec = None;
-        let mut hit_anything = false;
         let mut closest_so_far = t_max;
 
         for object in &self.objects {
             if let Some(temp_rec) = object.hit(r, t_min, closest_so_far) {
-                hit_anything = true;
                 closest_so_far = temp_rec.t;
                 rec = Some(temp_rec);
             }
@@ -65,4 +126,14 @@ impl Hitable for HitableList {
         }
         Some(output_box)
     }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.object(std::mem::size_of_val(self) + std::mem::size_of_val(&self.objects[..]));
+        for object in &self.objects {
+            object.inspect(inspector);
+        }
+        for (name, objects) in &self.meshes {
+            inspector.mesh(name, *objects);
+        }
+    }
 }
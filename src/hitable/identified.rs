@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hitable::{HitRecord, Hitable},
+    inspect::Inspector,
+    ray::Ray,
+};
+
+/// Object tagging its hits with an ID, so they can be told apart in the object ID AOV.
+pub struct Identified {
+    pub id: usize,
+    pub object: Arc<dyn Hitable>,
+}
+
+impl Identified {
+    pub fn new(id: usize, object: Arc<dyn Hitable>) -> Self {
+        Identified { id, object }
+    }
+}
+
+impl Hitable for Identified {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut rec = self.object.hit(r, t_min, t_max)?;
+        rec.object_id = self.id;
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
+        self.object.bounding_box(time)
+    }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.object(std::mem::size_of_val(self));
+        self.object.inspect(inspector);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod animated;
 pub mod hitable_list;
+pub mod identified;
 pub mod moving_sphere;
 pub mod sphere;
 pub mod triangle;
@@ -10,12 +12,14 @@ use std::sync::Arc;
 
 use crate::{
     aabb::Aabb,
+    inspect::Inspector,
     material::{lambertian::Lambertian, Material},
     ray::Ray,
     utils::color::Color,
     vec3::Vec3,
 };
 
+/// Where and how a ray hit an object.
 #[derive(Clone)]
 pub struct HitRecord {
     pub p: Vec3,
@@ -25,10 +29,25 @@ pub struct HitRecord {
     pub v: f64,
     pub front_face: bool,
     pub mat_ptr: Arc<dyn Material>,
+    /// Top level object of the world that was hit, 0 when unknown.
+    pub object_id: usize,
+    /// Barycentric coordinates of the hit when the object is a triangle.
+    pub barycentric: Option<(f64, f64)>,
 }
 
 impl HitRecord {
-    pub fn default() -> Self {
+    pub fn set_face_normal(&mut self, r: &Ray, outward_normal: &Vec3) {
+        self.front_face = Vec3::dot(&r.direction(), outward_normal) < 0.0;
+        self.normal = if self.front_face {
+            *outward_normal
+        } else {
+            -*outward_normal
+        };
+    }
+}
+
+impl Default for HitRecord {
+    fn default() -> Self {
         HitRecord {
             p: Vec3::new(0.0, 0.0, 0.0),
             normal: Vec3::new(0.0, 0.0, 0.0),
@@ -37,20 +56,20 @@ impl HitRecord {
             v: 0.0,
             front_face: false,
             mat_ptr: Arc::new(Lambertian::new(Color::new(255.0, 0.0, 0.0))),
+            object_id: 0,
+            barycentric: None,
         }
     }
-
-    pub fn set_face_normal(&mut self, r: &Ray, outward_normal: &Vec3) {
-        self.front_face = Vec3::dot(&r.direction(), outward_normal) < 0.0;
-        self.normal = if self.front_face {
-            *outward_normal
-        } else {
-            -*outward_normal
-        };
-    }
 }
 
+/// Anything a ray can intersect.
 pub trait Hitable: Send + Sync {
+    /// Closest intersection of `r` with a ray parameter in `(t_min, t_max)`.
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    /// Box enclosing the object over the `time` interval, `None` for unbounded objects.
     fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb>;
+    /// Reports the object and the objects it is made of to `inspector`, for diagnostics.
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.other(std::mem::size_of_val(self));
+    }
 }
@@ -3,8 +3,11 @@ use std::sync::Arc;
 use crate::{
     aabb::Aabb,
     hitable::{HitRecord, Hitable},
+    inspect::Inspector,
     material::Material,
     ray::Ray,
+    stats,
+    utils::PI,
     vec3::Vec3,
 };
 
@@ -38,42 +41,42 @@ impl MovingSphere {
 
 impl Hitable for MovingSphere {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        if let Some(time) = r.time {
-            let oc = r.origin() - self.center(time);
-            let a = r.direction().length_squared();
-            let half_b = Vec3::dot(&oc, &r.direction());
+        stats::count_primitive_test();
+        // Rays without a time come from a camera without shutter interval, they see
+        // the sphere where it starts
+        let time = r.time.unwrap_or(self.time.0);
+        let oc = r.origin() - self.center(time);
+        let a = r.direction().length_squared();
+        let half_b = Vec3::dot(&oc, &r.direction());
 
-            let c = oc.length_squared() - self.radius * self.radius;
+        let c = oc.length_squared() - self.radius * self.radius;
 
-            let discriminant = half_b * half_b - a * c;
+        let discriminant = half_b * half_b - a * c;
 
-            if discriminant < 0.0 {
-                return None;
-            }
+        if discriminant < 0.0 {
+            return None;
+        }
 
-            let sqrtd = f64::sqrt(discriminant);
+        let sqrtd = f64::sqrt(discriminant);
 
-            // Find the nearest root that lies in the acceptable range
-            let mut root = (-half_b - sqrtd) / a;
+        // Find the nearest root that lies in the acceptable range
+        let mut root = (-half_b - sqrtd) / a;
+        if root < t_min || t_max < root {
+            root = (-half_b + sqrtd) / a;
             if root < t_min || t_max < root {
-                root = (-half_b + sqrtd) / a;
-                if root < t_min || t_max < root {
-                    return None;
-                }
+                return None;
             }
+        }
 
-            let mut rec = HitRecord::default();
-            rec.t = root;
-            rec.p = r.at(rec.t);
+        let mut rec = HitRecord::default();
+        rec.t = root;
+        rec.p = r.at(rec.t);
 
-            let outward_normal = (rec.p - self.center(time)) / self.radius;
-            rec.set_face_normal(r, &outward_normal);
-            rec.mat_ptr = Arc::clone(&self.mat_ptr);
+        let outward_normal = (rec.p - self.center(time)) / self.radius;
+        rec.set_face_normal(r, &outward_normal);
+        rec.mat_ptr = Arc::clone(&self.mat_ptr);
 
-            Some(rec)
-        } else {
-            panic!("Ray hitting MovingSphere should always have time value");
-        }
+        Some(rec)
     }
 
     fn bounding_box(&self, time: (f64, f64)) -> Option<crate::aabb::Aabb> {
@@ -89,4 +92,14 @@ impl Hitable for MovingSphere {
 
         Some(Aabb::surrounding_box(box0, box1))
     }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.primitive(
+            "moving_sphere",
+            &self.mat_ptr,
+            4.0 * PI * self.radius * self.radius,
+            self.center.0,
+            std::mem::size_of_val(self),
+        );
+    }
 }
@@ -3,8 +3,10 @@ use std::sync::Arc;
 use crate::{
     aabb::Aabb,
     hitable::{HitRecord, Hitable},
+    inspect::Inspector,
     material::Material,
     ray::Ray,
+    stats,
     utils::PI,
     vec3::Vec3,
 };
@@ -33,6 +35,7 @@ impl Sphere {
 
 impl Hitable for Sphere {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let oc = r.origin() - self.center;
         let a = r.direction().length_squared();
         let half_b = Vec3::dot(&oc, &r.direction());
@@ -68,10 +71,20 @@ impl Hitable for Sphere {
         Some(rec)
     }
 
-    fn bounding_box(&self, time: (f64, f64)) -> Option<crate::aabb::Aabb> {
+    fn bounding_box(&self, _time: (f64, f64)) -> Option<crate::aabb::Aabb> {
         Some(Aabb::new(
             self.center - Vec3::new(self.radius, self.radius, self.radius),
             self.center + Vec3::new(self.radius, self.radius, self.radius),
         ))
     }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.primitive(
+            "sphere",
+            &self.mat_ptr,
+            4.0 * PI * self.radius * self.radius,
+            self.center,
+            std::mem::size_of_val(self),
+        );
+    }
 }
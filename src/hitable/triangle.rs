@@ -3,7 +3,9 @@ use std::sync::Arc;
 use crate::{
     aabb::Aabb,
     hitable::{HitRecord, Hitable},
+    inspect::Inspector,
     material::Material,
+    stats,
     vec3::Vec3,
 };
 
@@ -22,6 +24,7 @@ impl Triangle {
 
 impl Hitable for Triangle {
     fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let edge1 = self.b - self.a;
         let edge2 = self.c - self.a;
         let h = r.direction.cross(&edge2);
@@ -35,7 +38,7 @@ impl Hitable for Triangle {
         let s = r.origin - self.a;
         let u = f * Vec3::dot(&s, &h);
 
-        if u < 0.0 || u > 1.0 {
+        if !(0.0..=1.0).contains(&u) {
             return None;
         }
 
@@ -68,13 +71,38 @@ impl Hitable for Triangle {
                 v,
                 front_face,
                 mat_ptr: Arc::clone(&self.mat_ptr),
+                object_id: 0,
+                barycentric: Some((u, v)),
             })
         } else {
             None
         }
     }
 
-    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
-        todo!()
+    fn bounding_box(&self, _time: (f64, f64)) -> Option<Aabb> {
+        // Pad the box so triangles aligned with an axis still have some thickness
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+        Some(Aabb::new(
+            Vec3::new(
+                f64::min(self.a.x, f64::min(self.b.x, self.c.x)),
+                f64::min(self.a.y, f64::min(self.b.y, self.c.y)),
+                f64::min(self.a.z, f64::min(self.b.z, self.c.z)),
+            ) - padding,
+            Vec3::new(
+                f64::max(self.a.x, f64::max(self.b.x, self.c.x)),
+                f64::max(self.a.y, f64::max(self.b.y, self.c.y)),
+                f64::max(self.a.z, f64::max(self.b.z, self.c.z)),
+            ) + padding,
+        ))
+    }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        inspector.primitive(
+            "triangle",
+            &self.mat_ptr,
+            (self.b - self.a).cross(&(self.c - self.a)).length() / 2.0,
+            (self.a + self.b + self.c) / 3.0,
+            std::mem::size_of_val(self),
+        );
     }
 }
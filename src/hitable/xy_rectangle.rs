@@ -3,7 +3,9 @@ use std::sync::Arc;
 use crate::{
     aabb::Aabb,
     hitable::{HitRecord, Hitable},
+    inspect::Inspector,
     material::Material,
+    stats,
     vec3::Vec3,
 };
 
@@ -22,6 +24,7 @@ impl XYRectangle {
 
 impl Hitable for XYRectangle {
     fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let t = (self.k - r.origin.z) / r.direction.z;
         if t < t_min || t > t_max {
             return None;
@@ -32,11 +35,12 @@ impl Hitable for XYRectangle {
             return None;
         }
 
-        let mut rec = HitRecord::default();
-
-        rec.u = (x - self.x.0) / (self.x.1 - self.x.0);
-        rec.v = (y - self.y.0) / (self.y.1 - self.y.0);
-        rec.t = t;
+        let mut rec = HitRecord {
+            u: (x - self.x.0) / (self.x.1 - self.x.0),
+            v: (y - self.y.0) / (self.y.1 - self.y.0),
+            t,
+            ..Default::default()
+        };
 
         let outward_normal = Vec3::new(0.0, 0.0, 1.0);
         rec.set_face_normal(r, &outward_normal);
@@ -47,7 +51,7 @@ impl Hitable for XYRectangle {
         Some(rec)
     }
 
-    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
+    fn bounding_box(&self, _time: (f64, f64)) -> Option<Aabb> {
         // The bounding box must have non-zero width in each dimension, so pad the Z
         // dimension a small amount.
         Some(Aabb {
@@ -55,4 +59,15 @@ impl Hitable for XYRectangle {
             maximum: Vec3::new(self.x.1, self.y.1, self.k + 0.0001),
         })
     }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        let mid = |(lo, hi): (f64, f64)| (lo + hi) / 2.0;
+        inspector.primitive(
+            "rectangle",
+            &self.material,
+            (self.x.1 - self.x.0) * (self.y.1 - self.y.0),
+            Vec3::new(mid(self.x), mid(self.y), self.k),
+            std::mem::size_of_val(self),
+        );
+    }
 }
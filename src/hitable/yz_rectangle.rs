@@ -3,7 +3,9 @@ use std::sync::Arc;
 use crate::{
     aabb::Aabb,
     hitable::{HitRecord, Hitable},
+    inspect::Inspector,
     material::Material,
+    stats,
     vec3::Vec3,
 };
 
@@ -22,6 +24,7 @@ impl YZRectangle {
 
 impl Hitable for YZRectangle {
     fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let t = (self.k - r.origin.x) / r.direction.x;
         if t < t_min || t > t_max {
             return None;
@@ -32,11 +35,12 @@ impl Hitable for YZRectangle {
             return None;
         }
 
-        let mut rec = HitRecord::default();
-
-        rec.u = (y - self.y.0) / (self.y.1 - self.y.0);
-        rec.v = (z - self.z.0) / (self.z.1 - self.z.0);
-        rec.t = t;
+        let mut rec = HitRecord {
+            u: (y - self.y.0) / (self.y.1 - self.y.0),
+            v: (z - self.z.0) / (self.z.1 - self.z.0),
+            t,
+            ..Default::default()
+        };
 
         let outward_normal = Vec3::new(1.0, 0.0, 0.0);
         rec.set_face_normal(r, &outward_normal);
@@ -47,12 +51,47 @@ impl Hitable for YZRectangle {
         Some(rec)
     }
 
-    fn bounding_box(&self, time: (f64, f64)) -> Option<Aabb> {
-        // The bounding box must have non-zero width in each dimension, so pad the Z
+    fn bounding_box(&self, _time: (f64, f64)) -> Option<Aabb> {
+        // The bounding box must have non-zero width in each dimension, so pad the X
         // dimension a small amount.
         Some(Aabb {
-            minimum: Vec3::new(self.y.0, self.z.0, self.k - 0.0001),
-            maximum: Vec3::new(self.y.1, self.z.1, self.k + 0.0001),
+            minimum: Vec3::new(self.k - 0.0001, self.y.0, self.z.0),
+            maximum: Vec3::new(self.k + 0.0001, self.y.1, self.z.1),
         })
     }
+
+    fn inspect(&self, inspector: &mut Inspector) {
+        let mid = |(lo, hi): (f64, f64)| (lo + hi) / 2.0;
+        inspector.primitive(
+            "rectangle",
+            &self.material,
+            (self.y.1 - self.y.0) * (self.z.1 - self.z.0),
+            Vec3::new(self.k, mid(self.y), mid(self.z)),
+            std::mem::size_of_val(self),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::YZRectangle;
+    use crate::{
+        hitable::Hitable, material::lambertian::Lambertian, ray::Ray, utils::color::Color,
+        vec3::Vec3,
+    };
+
+    #[test]
+    fn should_bound_the_rectangle_in_its_plane() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let rectangle = YZRectangle::new((0.0, 2.0), (5.0, 6.0), 3.0, material);
+        let bounds = rectangle.bounding_box((0.0, 0.0)).unwrap();
+
+        // Straight along -x onto the middle of the rectangle
+        let r = Ray::new(Vec3::new(10.0, 1.0, 5.5), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(rectangle.hit(&r, 0.001, f64::INFINITY).is_some());
+        assert!(bounds.andrew_kensler_hit(&r, 0.001, f64::INFINITY));
+        assert!(bounds.minimum.x < 3.0 && bounds.maximum.x > 3.0);
+    }
 }
@@ -0,0 +1,426 @@
+//! Inventory of a scene built without rendering it: what it is made of, how big it is and
+//! how its lights and BVH look, to check a scene before starting a long render.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    mem::size_of,
+    sync::Arc,
+};
+
+use crate::{
+    aabb::Aabb,
+    aov::AovSample,
+    bhv::BHVNode,
+    camera::{Camera, Projection},
+    hitable::Hitable,
+    material::Material,
+    scene::Scene,
+    texture::Texture,
+    utils::{color::Color, PI},
+    vec3::Vec3,
+};
+
+// Reference counts stored next to every shared object
+const ARC_HEADER: usize = 2 * size_of::<usize>();
+
+/// Surface whose material emits light.
+#[derive(Debug, Clone, Copy)]
+pub struct Emitter {
+    pub kind: &'static str,
+    /// Point the emission was looked up at, the centre of the surface.
+    pub center: Vec3,
+    pub area: f64,
+    pub radiance: Color,
+}
+
+impl Emitter {
+    /// Radiant flux of a diffuse emitter, π times its radiance times its area.
+    pub fn power(&self) -> Color {
+        self.radiance * (PI * self.area)
+    }
+}
+
+/// Shape of a bounding volume hierarchy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BvhStats {
+    pub nodes: usize,
+    /// Children of nodes which are not nodes themselves.
+    pub leaves: usize,
+    /// Nodes from the root to the deepest leaf, the leaf included.
+    pub max_depth: usize,
+    leaf_depths: usize,
+}
+
+impl BvhStats {
+    pub fn average_leaf_depth(&self) -> f64 {
+        if self.leaves == 0 {
+            0.0
+        } else {
+            self.leaf_depths as f64 / self.leaves as f64
+        }
+    }
+}
+
+/// Collects what the objects of a world report through [`Hitable::inspect`].
+#[derive(Default)]
+pub struct Inspector {
+    /// Number of primitives of every kind.
+    pub primitives: BTreeMap<&'static str, usize>,
+    /// Number of triangles of every named mesh.
+    pub meshes: BTreeMap<String, usize>,
+    pub emitters: Vec<Emitter>,
+    pub bvh: BvhStats,
+    /// Bytes of the objects, materials and textures.
+    pub memory: usize,
+    materials: BTreeMap<&'static str, usize>,
+    textures: BTreeMap<&'static str, usize>,
+    seen: HashSet<usize>,
+    // BVH nodes above the object being inspected
+    depth: usize,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports a surface of `kind` made of `material`, `center` is a point on it and `bytes`
+    /// the size of the object.
+    pub fn primitive(
+        &mut self,
+        kind: &'static str,
+        material: &Arc<dyn Material>,
+        area: f64,
+        center: Vec3,
+        bytes: usize,
+    ) {
+        *self.primitives.entry(kind).or_insert(0) += 1;
+        self.memory += bytes + ARC_HEADER;
+
+        let radiance = material.emitted(0.5, 0.5, &center);
+        if radiance != Color::new(0.0, 0.0, 0.0) {
+            self.emitters.push(Emitter {
+                kind,
+                center,
+                area,
+                radiance,
+            });
+        }
+
+        if self.first_time(Arc::as_ptr(material) as *const ()) {
+            *self.materials.entry(material.name()).or_insert(0) += 1;
+            self.memory += std::mem::size_of_val(material.as_ref()) + ARC_HEADER;
+            for texture in material.textures() {
+                self.texture(&texture);
+            }
+        }
+    }
+
+    /// Reports `triangles` more triangles of `mesh`.
+    pub fn mesh(&mut self, mesh: &str, triangles: usize) {
+        match self.meshes.get_mut(mesh) {
+            Some(count) => *count += triangles,
+            None => {
+                self.meshes.insert(mesh.to_string(), triangles);
+            }
+        }
+    }
+
+    /// Reports an object which is not a surface, a list or a wrapper, of `bytes`.
+    pub fn object(&mut self, bytes: usize) {
+        self.memory += bytes + ARC_HEADER;
+    }
+
+    /// Reports an object of an unknown kind, neither its material nor its children.
+    pub fn other(&mut self, bytes: usize) {
+        *self.primitives.entry("custom").or_insert(0) += 1;
+        self.memory += bytes + ARC_HEADER;
+    }
+
+    /// Reports a BVH node and inspects its distinct `children`.
+    pub fn bvh_node(&mut self, children: &[&dyn Hitable]) {
+        self.bvh.nodes += 1;
+        self.memory += size_of::<BHVNode>() + ARC_HEADER;
+        self.depth += 1;
+        for child in children {
+            let nodes = self.bvh.nodes;
+            child.inspect(self);
+            if self.bvh.nodes == nodes {
+                self.bvh.leaves += 1;
+                self.bvh.leaf_depths += self.depth + 1;
+                self.bvh.max_depth = self.bvh.max_depth.max(self.depth + 1);
+            }
+        }
+        self.depth -= 1;
+    }
+
+    pub fn material_counts(&self) -> &BTreeMap<&'static str, usize> {
+        &self.materials
+    }
+
+    pub fn texture_counts(&self) -> &BTreeMap<&'static str, usize> {
+        &self.textures
+    }
+
+    pub fn total_power(&self) -> Color {
+        self.emitters
+            .iter()
+            .fold(Color::new(0.0, 0.0, 0.0), |sum, e| sum + e.power())
+    }
+
+    fn texture(&mut self, texture: &Arc<dyn Texture>) {
+        if self.first_time(Arc::as_ptr(texture) as *const ()) {
+            *self.textures.entry(texture.name()).or_insert(0) += 1;
+            self.memory += std::mem::size_of_val(texture.as_ref()) + ARC_HEADER;
+            for texture in texture.textures() {
+                self.texture(&texture);
+            }
+        }
+    }
+
+    fn first_time(&mut self, pointer: *const ()) -> bool {
+        self.seen.insert(pointer as usize)
+    }
+}
+
+/// Everything `rtxon info` reports about a scene.
+pub struct Inspection {
+    pub inspector: Inspector,
+    /// Bounds of the world over the shutter interval, `None` when something is unbounded.
+    pub bounds: Option<Aabb>,
+    pub camera: Camera,
+    pub background: Color,
+}
+
+/// Builds the BVH of `scene` like the renderer does, without rendering, and walks it.
+pub fn inspect(scene: &Scene) -> Inspection {
+    let shutter = scene.camera.time().unwrap_or((0.0, 0.0));
+    let bounds = scene.world.bounding_box(shutter);
+    let world = scene.world.clone().into_bvh(shutter);
+
+    let mut inspector = Inspector::new();
+    world.inspect(&mut inspector);
+    Inspection {
+        inspector,
+        bounds,
+        camera: scene.camera.clone(),
+        background: scene.background,
+    }
+}
+
+/// Bytes of the frame buffers of a `width` by `height` render.
+pub fn film_memory(width: usize, height: usize, aovs: bool) -> usize {
+    let per_pixel = size_of::<Color>()
+        + size_of::<(Color, f64)>()
+        + if aovs { size_of::<AovSample>() } else { 0 };
+    width * height * per_pixel
+}
+
+impl Inspection {
+    /// Human readable report.
+    pub fn summary(&self) -> String {
+        let i = &self.inspector;
+        let mut s = String::new();
+
+        let total: usize = i.primitives.values().sum();
+        writeln!(s, "Primitives:   {} ({})", total, counts(&i.primitives)).unwrap();
+        for (mesh, triangles) in &i.meshes {
+            writeln!(s, "  Mesh {}: {} triangles", mesh, triangles).unwrap();
+        }
+        let materials = i.material_counts();
+        writeln!(
+            s,
+            "Materials:    {} ({})",
+            materials.values().sum::<usize>(),
+            counts(materials)
+        )
+        .unwrap();
+        let textures = i.texture_counts();
+        writeln!(
+            s,
+            "Textures:     {} ({})",
+            textures.values().sum::<usize>(),
+            counts(textures)
+        )
+        .unwrap();
+
+        match &self.bounds {
+            Some(b) => writeln!(
+                s,
+                "Bounds:       {} to {} (size {})",
+                vector(b.minimum),
+                vector(b.maximum),
+                vector(b.maximum - b.minimum)
+            ),
+            None => writeln!(s, "Bounds:       unbounded"),
+        }
+        .unwrap();
+
+        writeln!(
+            s,
+            "Emitters:     {}, total power {}, background {}",
+            i.emitters.len(),
+            color(i.total_power()),
+            color(self.background)
+        )
+        .unwrap();
+        for e in &i.emitters {
+            writeln!(
+                s,
+                "  {} at {}: area {:.3}, radiance {}, power {}",
+                e.kind,
+                vector(e.center),
+                e.area,
+                color(e.radiance),
+                color(e.power())
+            )
+            .unwrap();
+        }
+
+        let cam = &self.camera;
+        let (_, _, w) = cam.basis();
+        let projection = match cam.projection() {
+            Projection::Perspective => String::from("perspective"),
+            Projection::Orthographic => String::from("orthographic"),
+            Projection::Fisheye { fov, mapping } => {
+                format!("{:?} fisheye, {:.1} degrees", mapping, fov).to_lowercase()
+            }
+            Projection::Equirectangular => String::from("equirectangular"),
+        };
+        writeln!(
+            s,
+            "Camera:       at {} looking {}, {}",
+            vector(cam.origin()),
+            vector(-w),
+            projection
+        )
+        .unwrap();
+        write!(
+            s,
+            "              {:.1} degrees vertical fov, aperture {:.3}, focus distance {:.3}",
+            cam.vfov(),
+            2.0 * cam.lens_radius(),
+            cam.focus_dist()
+        )
+        .unwrap();
+        match cam.time() {
+            Some((open, close)) => writeln!(s, ", shutter {} to {}", open, close),
+            None => writeln!(s),
+        }
+        .unwrap();
+
+        writeln!(
+            s,
+            "BVH:          {} nodes, {} leaves, depth {}, average leaf depth {:.1}",
+            i.bvh.nodes,
+            i.bvh.leaves,
+            i.bvh.max_depth,
+            i.bvh.average_leaf_depth()
+        )
+        .unwrap();
+        write!(s, "Scene memory: {}", memory(i.memory)).unwrap();
+        s
+    }
+}
+
+/// Bytes in KiB or MiB with one decimal.
+pub fn memory(bytes: usize) -> String {
+    if bytes < 1 << 20 {
+        format!("{:.1} KiB", bytes as f64 / (1 << 10) as f64)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1 << 20) as f64)
+    }
+}
+
+fn counts(counts: &BTreeMap<&'static str, usize>) -> String {
+    counts
+        .iter()
+        .map(|(kind, n)| format!("{} {}", n, kind))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn vector(v: Vec3) -> String {
+    format!("({:.3}, {:.3}, {:.3})", v.x, v.y, v.z)
+}
+
+fn color(c: Color) -> String {
+    format!("({:.3}, {:.3}, {:.3})", c.r, c.g, c.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inspect, Inspector};
+    use crate::{
+        hitable::{hitable_list::HitableList, sphere::Sphere, triangle::Triangle, Hitable},
+        material::{diffuse_light::DiffuseLight, lambertian::Lambertian},
+        texture::checker_texture::CheckerTexture,
+        utils::PI,
+        Camera, Color, Scene, Vec3,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn should_count_primitives_materials_and_lights() {
+        let checker = Arc::new(Lambertian::new_from_texture(Arc::new(CheckerTexture::new(
+            Color::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))));
+        let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+        let mut world = HitableList::new();
+        world.add(Sphere::new(
+            Vec3::new(0.0, -100.0, 0.0),
+            99.0,
+            checker.clone(),
+        ));
+        world.add(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 0.5, light));
+        world.add_mesh(
+            "ramp",
+            (0..3).map(|i| {
+                let x = i as f64;
+                Triangle::new(
+                    Vec3::new(x, 0.0, 0.0),
+                    Vec3::new(x + 1.0, 0.0, 0.0),
+                    Vec3::new(x, 1.0, 0.0),
+                    checker.clone(),
+                )
+            }),
+        );
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            5.0,
+            None,
+        );
+        let scene = Scene::new(world, camera, Color::default(), 1.0);
+
+        let inspection = inspect(&scene);
+        let i = &inspection.inspector;
+        assert_eq!(i.primitives["sphere"], 2);
+        assert_eq!(i.primitives["triangle"], 3);
+        assert_eq!(i.meshes["ramp"], 3);
+        assert_eq!(i.material_counts()["lambertian"], 1);
+        assert_eq!(i.material_counts()["diffuse_light"], 1);
+        assert_eq!(i.texture_counts()["checker"], 1);
+        assert_eq!(i.texture_counts()["solid_color"], 3);
+        assert_eq!(i.emitters.len(), 1);
+        assert!((i.total_power().r - 4.0 * PI * PI).abs() < 1e-9);
+        assert_eq!(i.bvh.leaves, 5);
+        assert!(i.bvh.nodes >= 2 && i.bvh.max_depth >= 3);
+        assert!((inspection.camera.vfov() - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_count_unknown_objects() {
+        let mut inspector = Inspector::new();
+        let world: &dyn Hitable =
+            &crate::aabb::Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        world.inspect(&mut inspector);
+        assert_eq!(inspector.primitives["custom"], 1);
+    }
+}
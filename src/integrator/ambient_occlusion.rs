@@ -0,0 +1,67 @@
+use crate::{
+    aov::AovSample,
+    camera::Camera,
+    hitable::Hitable,
+    integrator::Integrator,
+    ray::Ray,
+    sampler::Sampler,
+    stats,
+    utils::{color::Color, INFINITY},
+    vec3::Vec3,
+};
+
+/// Whether the sky is visible from the first hit along one cosine weighted direction,
+/// only surfaces closer than `distance` hide it. The average over the samples shows how
+/// open each point is, whatever the materials and lights.
+pub struct AmbientOcclusionIntegrator {
+    distance: f64,
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(distance: f64) -> Self {
+        AmbientOcclusionIntegrator { distance }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn li(
+        &self,
+        r: &Ray,
+        _background: &Color,
+        world: &dyn Hitable,
+        cam: &Camera,
+        sampler: &mut dyn Sampler,
+        aov: Option<&mut AovSample>,
+    ) -> Color {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let Some(rec) = world.hit(r, 0.001, INFINITY) else {
+            if let Some(aov) = aov {
+                aov.emission = white;
+            }
+            return white;
+        };
+
+        let mut direction = rec.normal + Vec3::sample_unit_vector(sampler.get_2d());
+        // Catch degenerate directions
+        if direction.near_zero() {
+            direction = rec.normal;
+        }
+        stats::count_shadow_ray();
+        let occlusion = Ray::new_with_time(rec.p, direction, r.time);
+        let visible = world
+            .hit(&occlusion, 0.001, self.distance / direction.length())
+            .is_none();
+        let color = if visible {
+            white
+        } else {
+            Color::new(0.0, 0.0, 0.0)
+        };
+
+        if let Some(aov) = aov {
+            aov.record_hit(&rec, cam);
+            aov.albedo = white;
+            aov.direct = color;
+        }
+        color
+    }
+}
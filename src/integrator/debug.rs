@@ -0,0 +1,35 @@
+use crate::{
+    aov::AovSample, camera::Camera, debug::RenderMode, hitable::Hitable, integrator::Integrator,
+    path::PathDepths, ray::Ray, sampler::Sampler, utils::color::Color,
+};
+
+/// One of the debug views of `RenderMode`, it has no AOVs.
+pub struct DebugIntegrator {
+    mode: RenderMode,
+    depths: PathDepths,
+}
+
+impl DebugIntegrator {
+    /// The bounce count view stops paths like the path integrator with `depths` does.
+    pub fn new(mode: RenderMode, depths: PathDepths) -> Self {
+        DebugIntegrator { mode, depths }
+    }
+}
+
+impl Integrator for DebugIntegrator {
+    fn li(
+        &self,
+        r: &Ray,
+        background: &Color,
+        world: &dyn Hitable,
+        cam: &Camera,
+        sampler: &mut dyn Sampler,
+        _aov: Option<&mut AovSample>,
+    ) -> Color {
+        let c = self
+            .mode
+            .shade(r, background, world, &self.depths, sampler, cam);
+        // Debug views are seen as they are, undo the gamma correction of the film
+        Color::new(c.r * c.r, c.g * c.g, c.b * c.b)
+    }
+}
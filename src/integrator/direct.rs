@@ -0,0 +1,45 @@
+use crate::{
+    aov::AovSample,
+    camera::Camera,
+    hitable::Hitable,
+    integrator::{path::PathIntegrator, Integrator},
+    path::PathDepths,
+    ray::Ray,
+    sampler::Sampler,
+    utils::color::Color,
+};
+
+/// Light emitted by the first hit and the light reaching it straight from an emitter or
+/// the background, found along the one ray the surface scatters. Paths of two rays
+/// without the roulette, to see what the indirect light adds.
+pub struct DirectIntegrator {
+    path: PathIntegrator,
+}
+
+impl DirectIntegrator {
+    /// The depth limits of `depths` still apply, the second ray is not traced when they
+    /// stop the path earlier.
+    pub fn new(depths: PathDepths) -> Self {
+        DirectIntegrator {
+            path: PathIntegrator::new(PathDepths {
+                max: depths.max.min(2),
+                roulette: usize::MAX,
+                ..depths
+            }),
+        }
+    }
+}
+
+impl Integrator for DirectIntegrator {
+    fn li(
+        &self,
+        r: &Ray,
+        background: &Color,
+        world: &dyn Hitable,
+        cam: &Camera,
+        sampler: &mut dyn Sampler,
+        aov: Option<&mut AovSample>,
+    ) -> Color {
+        self.path.li(r, background, world, cam, sampler, aov)
+    }
+}
@@ -0,0 +1,192 @@
+pub mod ambient_occlusion;
+pub mod debug;
+pub mod direct;
+pub mod path;
+
+use clap::ValueEnum;
+
+use crate::{
+    aov::AovSample,
+    camera::Camera,
+    config::Config,
+    hitable::Hitable,
+    integrator::{
+        ambient_occlusion::AmbientOcclusionIntegrator, direct::DirectIntegrator,
+        path::PathIntegrator,
+    },
+    ray::Ray,
+    sampler::Sampler,
+    utils::color::Color,
+};
+
+/// Turns the camera rays into the light they bring back.
+pub trait Integrator: Send + Sync {
+    /// Light arriving at the camera along `r`. When `aov` is given it also receives what
+    /// the first hit saw and the light split in emission, direct and indirect.
+    fn li(
+        &self,
+        r: &Ray,
+        background: &Color,
+        world: &dyn Hitable,
+        cam: &Camera,
+        sampler: &mut dyn Sampler,
+        aov: Option<&mut AovSample>,
+    ) -> Color;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, ValueEnum)]
+pub enum IntegratorKind {
+    /// Paths bouncing until they escape, are absorbed, reach a depth limit or lose the
+    /// Russian roulette
+    #[default]
+    Path,
+    /// Light reaching the first hit straight from an emitter or the background
+    Direct,
+    /// White where nothing near the first hit hides the sky, black where it is occluded
+    Ao,
+}
+
+impl IntegratorKind {
+    /// Integrator with the settings of `config`, the occlusion distance defaults to a
+    /// tenth of the size of `world`.
+    pub fn build(&self, config: &Config, world: &dyn Hitable) -> Box<dyn Integrator> {
+        match self {
+            IntegratorKind::Path => Box::new(PathIntegrator::new(config.depths)),
+            IntegratorKind::Direct => Box::new(DirectIntegrator::new(config.depths)),
+            IntegratorKind::Ao => {
+                let distance = config.ao_distance.unwrap_or_else(|| {
+                    world
+                        .bounding_box((0.0, 0.0))
+                        .map_or(f64::INFINITY, |b| (b.maximum - b.minimum).length() / 10.0)
+                });
+                Box::new(AmbientOcclusionIntegrator::new(distance))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::IntegratorKind;
+    use crate::{
+        config::Config,
+        hitable::{hitable_list::HitableList, xz_rectangle::XZRectangle},
+        material::lambertian::Lambertian,
+        path::PathDepths,
+        ray::Ray,
+        sampler::SamplerKind,
+        scene, stats, Color, Vec3,
+    };
+
+    #[test]
+    fn should_match_the_path_integrator_limited_to_two_rays() {
+        let scene = scene::cornell_box_scene();
+        let config = Config::new(1.0, 16, 8, 8, SamplerKind::Independent, 1234);
+        let world = scene.world.into_bvh((0.0, 0.0));
+        let direct = IntegratorKind::Direct.build(&config, &world);
+        let path =
+            IntegratorKind::Path.build(&config.clone().with_depths(PathDepths::new(2)), &world);
+
+        let mut sampler = config.sampler.build(64, config.seed);
+        for sample in 0..64 {
+            let r = Ray::new(scene.camera.origin(), Vec3::new(0.0, -0.1, 1.0));
+            sampler.start_pixel_sample((0, 0), sample);
+            let expected = path.li(
+                &r,
+                &scene.background,
+                &world,
+                &scene.camera,
+                sampler.as_mut(),
+                None,
+            );
+            sampler.start_pixel_sample((0, 0), sample);
+            let light = direct.li(
+                &r,
+                &scene.background,
+                &world,
+                &scene.camera,
+                sampler.as_mut(),
+                None,
+            );
+            assert_eq!(light, expected);
+        }
+    }
+
+    #[test]
+    fn should_not_trace_the_second_ray_past_the_depth_limits() {
+        let scene = scene::cornell_box_scene();
+        let world = scene.world.into_bvh((0.0, 0.0));
+        let config = Config::new(1.0, 16, 8, 8, SamplerKind::Independent, 1234);
+        let limits = [
+            PathDepths::new(1),
+            PathDepths {
+                diffuse: Some(0),
+                specular: Some(0),
+                transmission: Some(0),
+                ..PathDepths::new(8)
+            },
+        ];
+
+        let mut sampler = config.sampler.build(16, config.seed);
+        for depths in limits {
+            let direct = IntegratorKind::Direct.build(&config.clone().with_depths(depths), &world);
+            let before = stats::current().bounce_rays;
+            for sample in 0..16 {
+                let r = Ray::new(scene.camera.origin(), Vec3::new(0.0, -0.1, 1.0));
+                sampler.start_pixel_sample((0, 0), sample);
+                let light = direct.li(
+                    &r,
+                    &scene.background,
+                    &world,
+                    &scene.camera,
+                    sampler.as_mut(),
+                    None,
+                );
+                // The floor seen by the ray does not glow
+                assert_eq!(light, Color::new(0.0, 0.0, 0.0));
+            }
+            assert_eq!(stats::current().bounce_rays, before);
+        }
+    }
+
+    #[test]
+    fn should_only_occlude_within_the_distance() {
+        let grey = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HitableList::new();
+        // A floor under a ceiling far wider than their distance
+        world.add(XZRectangle::new(
+            (-1e3, 1e3),
+            (-1e3, 1e3),
+            0.0,
+            grey.clone(),
+        ));
+        world.add(XZRectangle::new((-1e3, 1e3), (-1e3, 1e3), 2.0, grey));
+        let camera = scene::cornell_box_scene().camera;
+        let r = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let mut sampler = SamplerKind::Independent.build(16, 0);
+        let config = Config::new(1.0, 16, 16, 8, SamplerKind::Independent, 0);
+        for (distance, expected) in [(1.0, 1.0), (100.0, 0.0)] {
+            let ao = IntegratorKind::Ao.build(
+                &config
+                    .clone()
+                    .with_integrator(IntegratorKind::Ao, Some(distance)),
+                &world,
+            );
+            for sample in 0..16 {
+                sampler.start_pixel_sample((0, 0), sample);
+                let light = ao.li(
+                    &r,
+                    &Color::default(),
+                    &world,
+                    &camera,
+                    sampler.as_mut(),
+                    None,
+                );
+                assert_eq!(light, Color::new(expected, expected, expected));
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    aov::AovSample,
+    camera::Camera,
+    hitable::{HitRecord, Hitable},
+    integrator::Integrator,
+    material::ScatterRay,
+    path::{Bounce, PathDepths, PathState},
+    ray::Ray,
+    sampler::Sampler,
+    stats,
+    utils::{color::Color, INFINITY},
+};
+
+/// One ray of a path and what it met, as the path integrator reports it.
+pub struct PathVertex<'a> {
+    /// Rays traced so far, this one included.
+    pub depth: usize,
+    pub ray: &'a Ray,
+    /// Surface the ray hit, `None` when it left the scene.
+    pub hit: Option<&'a HitRecord>,
+    /// Light emitted by the hit surface, or the background the ray ends on.
+    pub emitted: Color,
+    /// `emitted` weighted by the throughput of the path before this vertex.
+    pub light: Color,
+    /// Ray the surface sent further, `None` when the ray escaped or was absorbed.
+    pub scattered: Option<&'a ScatterRay>,
+    /// What the path did with the scattered ray.
+    pub bounce: Option<Bounce>,
+    /// Throughput of the path after this vertex, black once it ended.
+    pub throughput: Color,
+    /// Light gathered by the path so far.
+    pub radiance: Color,
+}
+
+/// Follows the path of a camera ray bounce after bounce, adding the light of every
+/// emitter it hits weighted by the throughput of the path so far.
+pub struct PathIntegrator {
+    depths: PathDepths,
+}
+
+impl PathIntegrator {
+    pub fn new(depths: PathDepths) -> Self {
+        PathIntegrator { depths }
+    }
+
+    /// Light arriving along `r`, reporting every vertex of its path to `record`.
+    pub fn trace(
+        &self,
+        r: &Ray,
+        background: &Color,
+        world: &dyn Hitable,
+        sampler: &mut dyn Sampler,
+        record: &mut dyn FnMut(&PathVertex),
+    ) -> Color {
+        let mut radiance = Color::new(0.0, 0.0, 0.0);
+        if self.depths.max == 0 {
+            return radiance;
+        }
+
+        let mut ray = Ray::new_with_time(r.origin, r.direction, r.time);
+        let mut path = PathState::default();
+        loop {
+            let depth = path.depth;
+            let rec = world.hit(&ray, 0.001, INFINITY);
+            let emitted = match &rec {
+                Some(rec) => rec.mat_ptr.emitted(rec.u, rec.v, &rec.p),
+                None => *background,
+            };
+            let light = path.throughput * emitted;
+            radiance = radiance + light;
+
+            let scattered = rec
+                .as_ref()
+                .and_then(|rec| rec.mat_ptr.scatter(&ray, rec, sampler));
+            let bounce = scattered.as_ref().map(|scattered| {
+                path.bounce(&self.depths, scattered.lobe, scattered.attenuation, sampler)
+            });
+            record(&PathVertex {
+                depth,
+                ray: &ray,
+                hit: rec.as_ref(),
+                emitted,
+                light,
+                scattered: scattered.as_ref(),
+                bounce,
+                throughput: path.throughput,
+                radiance,
+            });
+
+            let (Some(scattered), Some(Bounce::Continue { .. })) = (scattered, bounce) else {
+                break;
+            };
+            stats::count_bounce_ray();
+            ray = scattered.ray;
+        }
+
+        radiance
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn li(
+        &self,
+        r: &Ray,
+        background: &Color,
+        world: &dyn Hitable,
+        cam: &Camera,
+        sampler: &mut dyn Sampler,
+        mut aov: Option<&mut AovSample>,
+    ) -> Color {
+        self.trace(r, background, world, sampler, &mut |vertex| {
+            let Some(aov) = aov.as_deref_mut() else {
+                return;
+            };
+            // Light from the first hit is its emission, from the second direct, the rest
+            // indirect
+            match vertex.depth {
+                1 => aov.emission = vertex.light,
+                2 => aov.direct = vertex.light,
+                _ => aov.indirect = aov.indirect + vertex.light,
+            }
+            if vertex.depth == 1 {
+                if let Some(rec) = vertex.hit {
+                    aov.record_hit(rec, cam);
+                }
+                if let Some(scattered) = vertex.scattered {
+                    aov.albedo = scattered.attenuation;
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,62 @@
+//! A small path tracer following [Ray Tracing in One Weekend](https://raytracing.github.io/books/RayTracingInOneWeekend.html).
+//!
+//! A [`Scene`] holds the objects to render ([`hitable`]), their [`material`]s and
+//! [`texture`]s, the [`Camera`] and the background colour. The [`Renderer`] turns it
+//! into an image according to a [`Config`], the [`integrator`] it picks computes the
+//! light of every camera ray.
+//!
+//! ```no_run
+//! use rtxon::{sampler::SamplerKind, scene, Config, Renderer};
+//!
+//! let scene = scene::by_index(9)?;
+//! let config = Config::new(scene.aspect_ratio, 300, 64, 50, SamplerKind::Sobol, 42);
+//!
+//! let mut renderer = Renderer::new(config, scene.world, scene.camera, None);
+//! renderer.render_current_frame_threadpool(scene.background, 4, 16)?;
+//! renderer.save("cornell_box.png")?;
+//! # Ok::<(), rtxon::Error>(())
+//! ```
+
+pub mod aabb;
+pub mod animation;
+pub mod aov;
+pub mod bench;
+pub mod bhv;
+pub mod camera;
+pub mod compare;
+pub mod config;
+pub mod debug;
+pub mod denoise;
+pub mod error;
+pub mod film;
+pub mod hitable;
+pub mod inspect;
+pub mod integrator;
+pub mod material;
+pub mod path;
+pub mod ray;
+pub mod renderer;
+pub mod rig;
+pub mod sampler;
+pub mod scene;
+pub mod settings;
+pub mod stats;
+pub mod texture;
+mod thread_pool;
+pub mod trace;
+pub mod utils;
+pub mod vec3;
+
+pub use camera::Camera;
+pub use config::Config;
+pub use error::{Error, Result};
+pub use hitable::Hitable;
+pub use integrator::Integrator;
+pub use material::Material;
+pub use ray::Ray;
+pub use renderer::Renderer;
+pub use rig::Rig;
+pub use scene::{Scene, SceneBuilder};
+pub use texture::Texture;
+pub use utils::color::Color;
+pub use vec3::Vec3;
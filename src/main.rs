@@ -1,62 +1,356 @@
-mod aabb;
 mod args;
-mod bhv;
-mod camera;
-mod config;
-mod hitable;
-mod material;
-mod ray;
-mod renderer;
-mod scene;
-mod texture;
-mod thread_pool;
-mod utils;
-mod vec3;
-
-use crate::vec3::Vec3;
-use clap::Parser;
-use config::Config;
+
+use args::{Args, BenchArgs, Command, CompareArgs, ConvertArgs, RenderArgs, RigKind};
+use clap::{Parser, ValueEnum};
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
-use renderer::Renderer;
-use std::fmt::Write;
-
-fn main() {
-    let args = <args::Args>::parse();
-
-    let scenes = vec![
-        scene::random_scene,
-        scene::random_moving_scene,
-        scene::checker_scene,
-        scene::checker_emmisive_material_scene,
-        scene::scene1,
-        scene::scene2,
-        scene::scene3,
-        scene::scene4,
-        scene::rectangular_light_scene,
-        scene::cornell_box_scene,
-        scene::teapot_scene,
-    ];
-
-    if args.scene >= scenes.len() {
-        panic!(
-            "Scene {} does not exist there are {} scenes",
-            args.scene,
-            scenes.len()
+use rtxon::{
+    bench::{self, Baseline},
+    camera::{
+        aperture::{Aperture, ApertureMask},
+        Projection,
+    },
+    compare::{self, FloatImage},
+    debug::RenderMode,
+    denoise::Denoiser,
+    film::Filter,
+    inspect, scene,
+    settings::RenderSettings,
+    stats::RenderStats,
+    utils, Camera, Config, Error, Renderer, Result, Rig, Scene,
+};
+use std::{fmt::Write, process::ExitCode, sync::Arc};
+
+// Camera positions interpolated over the shutter interval of a frame
+const CAMERA_MOTION_STEPS: usize = 4;
+
+// Prints the metrics, returns whether the images are within the threshold
+fn compare(args: &CompareArgs) -> Result<bool> {
+    let reference = FloatImage::load(&args.reference)?;
+    let test = FloatImage::load(&args.test)?;
+    let comparison = compare::compare(&reference, &test)?;
+
+    println!("MSE:    {:.6}", comparison.mse);
+    println!("relMSE: {:.6}", comparison.rel_mse);
+    println!("PSNR:   {:.3} dB", comparison.psnr);
+    println!("SSIM:   {:.6}", comparison.ssim);
+    println!("FLIP:   {:.6}", comparison.flip);
+
+    if let Some(path) = &args.error_map {
+        compare::save_error_map(
+            &comparison.error_map(args.metric),
+            reference.width,
+            reference.height,
+            path,
+        )?;
+    }
+
+    Ok(match args.threshold {
+        None => true,
+        Some(threshold) => comparison.within(args.metric, threshold),
+    })
+}
+
+// Prints the timings of every workload, returns whether none regressed from the baseline
+fn bench(args: &BenchArgs) -> Result<bool> {
+    let baseline = args.baseline.as_deref().map(Baseline::load).transpose()?;
+    if let Some(baseline) = baseline.as_ref().filter(|b| b.threads != args.threads) {
+        eprintln!(
+            "Warning: the baseline ran with {} threads, this run uses {}",
+            baseline.threads, args.threads
         );
     }
 
-    // Scene
-    let (world, cam, background, aspect_ratio) = scenes[args.scene]();
+    println!(
+        "{:<16} {:>9} {:>5} {:>10} {:>8} {:>9} {:>9}",
+        "scene", "size", "spp", "time", "stddev", "Mrays/s", "change"
+    );
+    let mut results = Vec::with_capacity(bench::WORKLOADS.len());
+    let mut passed = true;
+    for workload in &bench::WORKLOADS {
+        let result = bench::run(
+            workload,
+            args.threads,
+            args.chunks,
+            args.warmup,
+            args.iterations,
+        )?;
+
+        let change = baseline.as_ref().and_then(|b| b.change(&result));
+        let regressed = change.is_some_and(|c| c < -args.tolerance / 100.0);
+        passed &= !regressed;
+        println!(
+            "{:<16} {:>9} {:>5} {:>8.3} s {:>7.1}% {:>9.3} {:>9}{}",
+            workload.name,
+            format!("{}x{}", result.size.0, result.size.1),
+            workload.samples,
+            result.median_time(),
+            result.time_deviation() * 100.0,
+            result.median_mrays_per_second(),
+            change.map_or_else(|| String::from("-"), |c| format!("{:+.1}%", c * 100.0)),
+            if regressed { "  REGRESSION" } else { "" }
+        );
+        results.push(result);
+    }
+
+    if let Some(path) = &args.save {
+        Baseline {
+            threads: args.threads,
+            results,
+        }
+        .save(path)?;
+    }
+    Ok(passed)
+}
+
+// Lens opening asked for on the command line, the scene's own when none is
+fn aperture(args: &RenderArgs) -> Result<Option<Aperture>> {
+    Ok(match (&args.aperture_mask, args.aperture_blades) {
+        (Some(path), _) => Some(Aperture::Mask(Arc::new(ApertureMask::load(path)?))),
+        (None, Some(blades)) => Some(Aperture::Polygon {
+            blades,
+            rotation: args.aperture_rotation,
+        }),
+        (None, None) => None,
+    })
+}
+
+// Applies the camera options of the command line to the camera of one frame
+fn setup_camera(
+    args: &RenderArgs,
+    settings: &RenderSettings,
+    scene: &Scene,
+    mut camera: Camera,
+    aperture: &Option<Aperture>,
+) -> Result<Camera> {
+    if let Some(kind) = args.projection {
+        camera = camera.with_projection(kind.projection(args.fisheye_fov));
+    }
+
+    if args.auto_frame {
+        camera = camera.frame(&scene.world)?;
+    }
+    if args.autofocus || args.focus_pixel.is_some() {
+        // Pixel centres, from the top left corner of the image
+        let width = settings.width;
+        let image_height = (width as f64 / scene.aspect_ratio) as usize;
+        let (x, y) = args.focus_pixel.unwrap_or((width / 2, image_height / 2));
+        if x >= width || y >= image_height {
+            return Err(Error::InvalidConfig(format!(
+                "focus pixel {},{} is outside of the {}x{} image",
+                x, y, width, image_height
+            )));
+        }
+        let s = (x as f64 + 0.5) / width as f64;
+        let t = 1.0 - (y as f64 + 0.5) / image_height as f64;
+        camera = camera.autofocus(&scene.world, s, t)?;
+    }
+
+    if let Some(aperture) = aperture {
+        camera = camera.with_aperture(aperture.clone());
+    }
+    if let Some(strength) = args.cat_eye {
+        camera = camera.with_cat_eye(strength);
+    }
+
+    Ok(camera.with_shutter_shape(args.shutter_shape))
+}
+
+// Camera of an animation frame, moving while the shutter is open if the scene animates it
+fn frame_camera(
+    args: &RenderArgs,
+    settings: &RenderSettings,
+    scene: &Scene,
+    shutter: (f64, f64),
+    aperture: &Option<Aperture>,
+) -> Result<Camera> {
+    if scene.camera_animation.is_none() {
+        let camera = scene.camera_at(shutter.0, Some(shutter));
+        return setup_camera(args, settings, scene, camera, aperture);
+    }
+
+    let keyframes = (0..=CAMERA_MOTION_STEPS)
+        .map(|i| {
+            let time = shutter.0 + (shutter.1 - shutter.0) * i as f64 / CAMERA_MOTION_STEPS as f64;
+            let camera = scene.camera_at(time, Some(shutter));
+            let camera = setup_camera(args, settings, scene, camera, aperture)?;
+            Ok((time, camera))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(keyframes[0].1.clone().with_motion(keyframes))
+}
+
+// Camera of a still image. It is the first frame of an animation when the scene animates
+// its camera or a shutter option is given, otherwise the scene camera with its own shutter.
+fn still_camera(
+    args: &RenderArgs,
+    settings: &RenderSettings,
+    scene: &Scene,
+    aperture: &Option<Aperture>,
+) -> Result<Camera> {
+    if scene.camera_animation.is_some()
+        || args.shutter_open.is_some()
+        || args.shutter_close.is_some()
+    {
+        return frame_camera(args, settings, scene, args.frame_shutter(0), aperture);
+    }
+    setup_camera(args, settings, scene, scene.camera.clone(), aperture)
+}
+
+// Image and sampling settings of the command line
+fn frame_config(
+    args: &RenderArgs,
+    settings: &RenderSettings,
+    aspect_ratio: f64,
+    seed: u64,
+) -> Result<Config> {
+    let config = Config::new(
+        aspect_ratio,
+        settings.width,
+        settings.samples,
+        settings.max_depth,
+        settings.sampler,
+        seed,
+    )
+    .with_depths(settings.depths())
+    .with_integrator(args.integrator, args.ao_distance)
+    .with_filter(Filter::new(args.filter, args.filter_radius))
+    .with_mode(args.mode);
+    let config = match &args.region {
+        Some(region) => {
+            let region = region.region(config.image_width, config.image_height);
+            config.with_region(region, args.crop)
+        }
+        None => config,
+    };
+    // The denoiser is guided by the AOVs
+    let config = if args.aov.is_empty() && !settings.denoise {
+        config
+    } else {
+        config.with_aovs()
+    };
+    config.validate()?;
+    Ok(config)
+}
+
+// Prints the paths of the samples of one pixel as JSON lines
+fn trace_pixel(
+    args: &RenderArgs,
+    settings: &RenderSettings,
+    scene: &Scene,
+    camera: Camera,
+    seed: u64,
+    pixel: (usize, usize),
+) -> Result<()> {
+    let config = frame_config(args, settings, scene.aspect_ratio, seed)?;
+    let renderer = Renderer::new(config, scene.world.clone(), camera, None);
+    eprintln!("Tracing pixel {},{} with seed {}", pixel.0, pixel.1, seed);
+    for sample in renderer.trace_pixel(&scene.background, pixel)? {
+        for line in sample.json_lines() {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+fn render_frame(
+    args: &RenderArgs,
+    settings: &RenderSettings,
+    scene: &Scene,
+    camera: Camera,
+    seed: u64,
+    output_path: &str,
+    pb: &ProgressBar,
+) -> Result<RenderStats> {
+    let rig = args.rig.map(|kind| match kind {
+        RigKind::Stereo => Rig::stereo(
+            &camera,
+            args.interocular,
+            args.convergence.unwrap_or_else(|| camera.focus_dist()),
+        ),
+        RigKind::CubeMap => Rig::cube_map(&camera),
+    });
+    let aspect_ratio = rig
+        .as_ref()
+        .and_then(Rig::aspect_ratio)
+        .unwrap_or(scene.aspect_ratio);
+
+    let config = frame_config(args, settings, aspect_ratio, seed)?;
+
+    let views = rig.as_ref().map_or(1, |rig| rig.views().len()) as u64;
+    pb.inc_length(views * config.window().area() as u64);
+
+    let mut renderer = Renderer::new(config, scene.world.clone(), camera, Some(pb.clone()));
+
+    match rig {
+        Some(rig) => {
+            let frames = rig.render(
+                &mut renderer,
+                scene.background,
+                settings.threads,
+                settings.chunks,
+            )?;
+            frames.save(output_path, args.layout)?;
+        }
+        None => {
+            renderer.render_current_frame_threadpool(
+                scene.background,
+                settings.threads,
+                settings.chunks,
+            )?;
+            renderer.save(output_path)?;
+            if let Some(aovs) = renderer.aovs().filter(|_| !args.aov.is_empty()) {
+                aovs.save(&args.aov, args.aov_layout, output_path)?;
+            }
+            if settings.denoise {
+                let denoiser = Denoiser::new(args.denoise_iterations);
+                renderer
+                    .save_denoised(&denoiser, &utils::suffixed_path(output_path, "denoised"))?;
+            }
+        }
+    }
+    println!("Frame saved succesfully");
+
+    Ok(renderer.take_stats())
+}
 
-    // Image Settings
-    let image_width: usize = args.width;
-    let samples_per_pixel = args.samples;
-    let max_depth = args.max_depth;
+// Prints the statistics of the run and saves them if asked to
+fn report(args: &RenderArgs, stats: &RenderStats) -> Result<()> {
+    println!("{}", stats.summary());
+    if let Some(path) = &args.stats_json {
+        stats.save_json(path)?;
+    }
+    Ok(())
+}
 
-    let config = Config::new(aspect_ratio, image_width, samples_per_pixel, max_depth);
+// Builds the scene of the command line, equirectangular projections render at 2:1
+fn build_scene(args: &RenderArgs) -> Result<Scene> {
+    let mut scene = args.scene.scene.build()?;
+    let projection = args
+        .projection
+        .map(|kind| kind.projection(args.fisheye_fov));
+    if projection == Some(Projection::Equirectangular) {
+        scene.aspect_ratio = 2.0;
+    }
+    Ok(scene)
+}
+
+fn render(args: &RenderArgs) -> Result<()> {
+    let settings = args.settings()?;
+
+    // The seed drives both the scene construction and the per sample random numbers
+    let seed = args.seed.unwrap_or_else(rand::random);
+    utils::seed_random(seed);
+
+    let scene = build_scene(args)?;
+    let aperture = aperture(args)?;
+
+    if let Some(pixel) = args.trace_pixel {
+        let camera = still_camera(args, &settings, &scene, &aperture)?;
+        return trace_pixel(args, &settings, &scene, camera, seed, pixel);
+    }
 
-    // Progress Bar
-    let pb = ProgressBar::new(config.image_height as u64 * config.image_width as u64);
+    // Progress Bar, frames add their pixels to it
+    let pb = ProgressBar::new(0);
 
     pb.set_style(
         ProgressStyle::with_template(
@@ -69,14 +363,214 @@ fn main() {
         .progress_chars("#>-"),
     );
 
-    let mut renderer = Renderer::new(config, world, cam, Some(pb));
+    match &args.frames {
+        None => {
+            let camera = still_camera(args, &settings, &scene, &aperture)?;
+            let stats = render_frame(
+                args,
+                &settings,
+                &scene,
+                camera,
+                seed,
+                &args.output_path,
+                &pb,
+            )?;
+            report(args, &stats)
+        }
+        Some(frames) => {
+            let mut stats = RenderStats::default();
+            for frame in frames.clone() {
+                // Every frame gets its own noise
+                let camera = frame_camera(
+                    args,
+                    &settings,
+                    &scene,
+                    args.frame_shutter(frame),
+                    &aperture,
+                )?;
+                let output_path = utils::suffixed_path(&args.output_path, &format!("{:04}", frame));
+                stats.merge(&render_frame(
+                    args,
+                    &settings,
+                    &scene,
+                    camera,
+                    seed.wrapping_add(frame as u64),
+                    &output_path,
+                    &pb,
+                )?);
+            }
+            report(args, &stats)
+        }
+    }
+}
+
+fn run_render(args: &RenderArgs) -> ExitCode {
+    match render(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Cannot render frame: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Prints the settings a render would use and what the scene is made of, without rendering
+fn info(args: &RenderArgs) -> Result<()> {
+    let settings = args.settings()?;
+    let seed = args.seed.unwrap_or_else(rand::random);
+    utils::seed_random(seed);
+    let mut scene = build_scene(args)?;
+    let aperture = aperture(args)?;
+    scene.camera = still_camera(args, &settings, &scene, &aperture)?;
+
+    let info = args.scene.scene;
+    let height = (settings.width as f64 / scene.aspect_ratio) as usize;
+    let sampler = settings
+        .sampler
+        .to_possible_value()
+        .expect("samplers have names");
+    println!(
+        "Scene:        {} ({}), seed {}",
+        info.name, info.description, seed
+    );
+    println!(
+        "Render:       {}x{}, {} spp, {} integrator, {} sampler, {}",
+        settings.width,
+        height,
+        settings.samples,
+        match args.mode {
+            RenderMode::Path => args
+                .integrator
+                .to_possible_value()
+                .expect("integrators have names")
+                .get_name()
+                .to_string(),
+            mode => format!(
+                "{} debug",
+                mode.to_possible_value()
+                    .expect("modes have names")
+                    .get_name()
+            ),
+        },
+        sampler.get_name(),
+        if settings.denoise {
+            "denoised"
+        } else {
+            "not denoised"
+        }
+    );
+    let depths = settings.depths();
+    let limits = [
+        ("diffuse", depths.diffuse),
+        ("specular", depths.specular),
+        ("transmission", depths.transmission),
+    ]
+    .iter()
+    .filter_map(|(lobe, depth)| depth.map(|depth| format!("{} {}", lobe, depth)))
+    .collect::<Vec<_>>();
+    println!(
+        "Depths:       {} at most{}, {}",
+        depths.max,
+        if limits.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", limits.join(", "))
+        },
+        // Paths reach their maximum depth before any roulette
+        if depths.roulette + 1 >= depths.max {
+            String::from("no Russian roulette")
+        } else {
+            format!("Russian roulette after {} bounces", depths.roulette)
+        }
+    );
+    println!(
+        "Threads:      {} ({} chunks)",
+        settings.threads, settings.chunks
+    );
+
+    let inspection = inspect::inspect(&scene);
+    println!("{}", inspection.summary());
+    let film = inspect::film_memory(
+        settings.width,
+        height,
+        !args.aov.is_empty() || settings.denoise,
+    );
+    println!(
+        "Film memory:  {}, {} in total",
+        inspect::memory(film),
+        inspect::memory(film + inspection.inspector.memory)
+    );
+    Ok(())
+}
 
-    renderer.render_current_frame_threadpool(background, args.threads, args.chunks);
+// Rewrites an image in the format of the output extension
+fn convert(args: &ConvertArgs) -> Result<()> {
+    let image = FloatImage::load(&args.input)?;
+    let image = if args.exposure == 0.0 {
+        image
+    } else {
+        image.exposed(args.exposure)
+    };
+    image.save(&args.output)
+}
 
-    match renderer.save(&args.output_path) {
-        Ok(()) => {
-            println!("Frame saved succesfully")
+// Prints the built-in scenes with their default settings
+fn list_scenes() {
+    println!(
+        "{:>3}  {:<22} {:>6} {:>5} {:>6}  Description",
+        "#", "Name", "Aspect", "Width", "Spp"
+    );
+    for (index, info) in scene::SCENES.iter().enumerate() {
+        println!(
+            "{:>3}  {:<22} {:>6.3} {:>5} {:>6}  {}",
+            index, info.name, info.aspect_ratio, info.width, info.samples, info.description
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let args = <Args>::parse();
+
+    match &args.command {
+        Some(Command::Render(render_args)) => run_render(render_args),
+        Some(Command::Info(render_args)) => match info(render_args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Cannot inspect scene: {}", e);
+                ExitCode::from(2)
+            }
+        },
+        Some(Command::ListScenes) => {
+            list_scenes();
+            ExitCode::SUCCESS
+        }
+        Some(Command::Convert(convert_args)) => match convert(convert_args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Cannot convert image: {}", e);
+                ExitCode::from(2)
+            }
+        },
+        Some(Command::Compare(compare_args)) => match compare(compare_args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::from(1),
+            Err(e) => {
+                eprintln!("Cannot compare images: {}", e);
+                ExitCode::from(2)
+            }
+        },
+        Some(Command::Bench(bench_args)) => match bench(bench_args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::from(1),
+            Err(e) => {
+                eprintln!("Cannot run benchmark: {}", e);
+                ExitCode::from(2)
+            }
+        },
+        None if args.list_scenes => {
+            list_scenes();
+            ExitCode::SUCCESS
         }
-        Err(e) => panic!("Cannot save frame {}", e),
+        None => run_render(&args.render),
     }
 }
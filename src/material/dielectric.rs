@@ -1,8 +1,9 @@
 use crate::{
     hitable::HitRecord,
-    material::{Material, ScatterRay},
+    material::{Lobe, Material, ScatterRay},
     ray::Ray,
-    utils::{color::Color, random_double},
+    sampler::Sampler,
+    utils::color::Color,
     vec3::Vec3,
 };
 
@@ -24,7 +25,12 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRay> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterRay> {
         let attenuation = Color::new(1.0, 1.0, 1.0);
 
         let reflection_ratio = if rec.front_face {
@@ -39,16 +45,26 @@ impl Material for Dielectric {
         let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
 
         let cannot_refract = reflection_ratio * sin_theta > 1.0;
-        let mut direction = Vec3::new(0.0, 0.0, 0.0);
-
-        if cannot_refract || self.reflectance(cos_theta, reflection_ratio) > random_double(None) {
-            direction = Vec3::reflect(&unit_direction, &rec.normal);
-        } else {
-            direction = Vec3::refract(&unit_direction, &rec.normal, reflection_ratio);
-        }
+        let (direction, lobe) =
+            if cannot_refract || self.reflectance(cos_theta, reflection_ratio) > sampler.get_1d() {
+                (Vec3::reflect(&unit_direction, &rec.normal), Lobe::Specular)
+            } else {
+                (
+                    Vec3::refract(&unit_direction, &rec.normal, reflection_ratio),
+                    Lobe::Transmission,
+                )
+            };
 
         let ray = Ray::new_with_time(rec.p, direction, r_in.time);
 
-        Some(ScatterRay { ray, attenuation })
+        Some(ScatterRay {
+            ray,
+            attenuation,
+            lobe,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "dielectric"
     }
 }
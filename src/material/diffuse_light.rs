@@ -4,6 +4,7 @@ use crate::{
     hitable::HitRecord,
     material::{Material, ScatterRay},
     ray::Ray,
+    sampler::Sampler,
     texture::solid_color::SolidColor,
     texture::Texture,
     utils::color::Color,
@@ -26,11 +27,24 @@ impl DiffuseLight {
 }
 
 impl Material for DiffuseLight {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRay> {
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        _rec: &HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> Option<ScatterRay> {
         None
     }
 
     fn emitted(&self, u: f64, v: f64, p: &Vec3) -> Color {
-        return self.emit.value(u, v, p);
+        self.emit.value(u, v, p)
+    }
+
+    fn name(&self) -> &'static str {
+        "diffuse_light"
+    }
+
+    fn textures(&self) -> Vec<Arc<dyn Texture>> {
+        vec![self.emit.clone()]
     }
 }
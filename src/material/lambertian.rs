@@ -2,8 +2,9 @@ use std::sync::Arc;
 
 use crate::{
     hitable::HitRecord,
-    material::{Material, ScatterRay},
+    material::{Lobe, Material, ScatterRay},
     ray::Ray,
+    sampler::Sampler,
     texture::solid_color::SolidColor,
     texture::Texture,
     utils::color::Color,
@@ -28,8 +29,13 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRay> {
-        let mut scatter_direction = rec.normal + Vec3::random_in_unit_vector();
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterRay> {
+        let mut scatter_direction = rec.normal + Vec3::sample_unit_vector(sampler.get_2d());
 
         // Catch degenerate scatter direction
         if scatter_direction.near_zero() {
@@ -38,6 +44,18 @@ impl Material for Lambertian {
 
         let ray = Ray::new_with_time(rec.p, scatter_direction, r_in.time);
         let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
-        Some(ScatterRay { ray, attenuation })
+        Some(ScatterRay {
+            ray,
+            attenuation,
+            lobe: Lobe::Diffuse,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "lambertian"
+    }
+
+    fn textures(&self) -> Vec<Arc<dyn Texture>> {
+        vec![self.albedo.clone()]
     }
 }
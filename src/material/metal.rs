@@ -1,7 +1,8 @@
 use crate::{
     hitable::HitRecord,
-    material::{Material, ScatterRay},
+    material::{Lobe, Material, ScatterRay},
     ray::Ray,
+    sampler::Sampler,
     utils::color::Color,
     vec3::Vec3,
 };
@@ -22,20 +23,31 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRay> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterRay> {
         let reflected = Vec3::reflect(&Vec3::unit_vector(&r_in.direction()), &rec.normal);
 
-        let ray = Ray::new_with_time(
-            rec.p,
-            reflected + Vec3::random_in_unit_sphere() * self.fuzz,
-            r_in.time,
-        );
+        let fuzz = Vec3::sample_in_unit_sphere(sampler.get_2d(), sampler.get_1d());
+
+        let ray = Ray::new_with_time(rec.p, reflected + fuzz * self.fuzz, r_in.time);
         let attenuation = self.albedo;
 
         if Vec3::dot(&ray.direction(), &rec.normal) > 0.0 {
-            Some(ScatterRay { ray, attenuation })
+            Some(ScatterRay {
+                ray,
+                attenuation,
+                lobe: Lobe::Specular,
+            })
         } else {
             None
         }
     }
+
+    fn name(&self) -> &'static str {
+        "metal"
+    }
 }
@@ -3,16 +3,56 @@ pub mod diffuse_light;
 pub mod lambertian;
 pub mod metal;
 
-use crate::{hitable::HitRecord, ray::Ray, utils::color::Color, vec3::Vec3};
+use std::sync::Arc;
 
+use crate::{
+    hitable::HitRecord, ray::Ray, sampler::Sampler, texture::Texture, utils::color::Color,
+    vec3::Vec3,
+};
+
+/// Kind of bounce a scattered ray takes, each kind has a depth limit of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lobe {
+    /// Scattered around the normal, like off a matte surface.
+    Diffuse,
+    /// Mirrored, maybe blurred, off a metal or the outside of glass.
+    Specular,
+    /// Refracted through the surface.
+    Transmission,
+}
+
+impl Lobe {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lobe::Diffuse => "diffuse",
+            Lobe::Specular => "specular",
+            Lobe::Transmission => "transmission",
+        }
+    }
+}
+
+/// Ray leaving a surface and the colour it is filtered by.
 pub struct ScatterRay {
     pub ray: Ray,
     pub attenuation: Color,
+    pub lobe: Lobe,
 }
 
+/// How light interacts with a surface.
 pub trait Material: Sync + Send {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRay>;
-    fn emitted(&self, u: f64, v: f64, p: &Vec3) -> Color {
+    /// Ray reflected or refracted at `rec`, `None` when the ray is absorbed.
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, sampler: &mut dyn Sampler)
+        -> Option<ScatterRay>;
+    /// Light emitted by the surface, black unless the material is a light.
+    fn emitted(&self, _u: f64, _v: f64, _p: &Vec3) -> Color {
         Color::new(0.0, 0.0, 0.0)
     }
+    /// Kind of material, for diagnostics.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+    /// Textures the material looks colours up in, for diagnostics.
+    fn textures(&self) -> Vec<Arc<dyn Texture>> {
+        Vec::new()
+    }
 }
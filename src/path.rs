@@ -0,0 +1,181 @@
+//! How long the traced paths may get: a depth limit for every path and for each kind of
+//! bounce, and Russian roulette ending paths that carry little light.
+
+use crate::{material::Lobe, sampler::Sampler, utils::color::Color};
+
+/// Bounces a path always takes before Russian roulette may end it.
+pub const ROULETTE_DEPTH: usize = 3;
+
+/// Depth limits of the paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathDepths {
+    /// Rays of a path, the camera ray included, so one more than its bounces. Light the
+    /// path would gather past them is lost, the path gets black.
+    pub max: usize,
+    /// Diffuse bounces of a path, only `max` limits them when `None`.
+    pub diffuse: Option<usize>,
+    /// Specular bounces of a path, only `max` limits them when `None`.
+    pub specular: Option<usize>,
+    /// Transmission bounces of a path, only `max` limits them when `None`.
+    pub transmission: Option<usize>,
+    /// Bounces before Russian roulette may end a path, paths limited to `roulette + 1`
+    /// rays or fewer never meet it.
+    pub roulette: usize,
+}
+
+impl PathDepths {
+    /// Paths of at most `max` rays, with Russian roulette after `ROULETTE_DEPTH` bounces.
+    pub fn new(max: usize) -> Self {
+        PathDepths {
+            max,
+            diffuse: None,
+            specular: None,
+            transmission: None,
+            roulette: ROULETTE_DEPTH,
+        }
+    }
+
+    fn limit(&self, lobe: Lobe) -> Option<usize> {
+        match lobe {
+            Lobe::Diffuse => self.diffuse,
+            Lobe::Specular => self.specular,
+            Lobe::Transmission => self.transmission,
+        }
+    }
+}
+
+/// What a path does after a surface scattered it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bounce {
+    /// Follows the scattered ray, the light coming back along it is multiplied by
+    /// `weight`: the attenuation over the chance the path survived the roulette.
+    Continue { weight: Color },
+    /// Stops at the maximum depth of the path or of the kind of bounce.
+    MaxDepth,
+    /// Stopped by Russian roulette.
+    Roulette,
+}
+
+/// Bounces a path has taken so far and the light it carries back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathState {
+    /// Rays traced so far, the camera ray included.
+    pub depth: usize,
+    pub diffuse: usize,
+    pub specular: usize,
+    pub transmission: usize,
+    /// Product of the weights of the bounces, black once the path ended.
+    pub throughput: Color,
+}
+
+impl Default for PathState {
+    fn default() -> Self {
+        PathState {
+            depth: 1,
+            diffuse: 0,
+            specular: 0,
+            transmission: 0,
+            throughput: Color::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl PathState {
+    /// Takes a `lobe` bounce filtered by `attenuation` unless a depth limit is reached or
+    /// the roulette ends the path. Draws a number from `sampler` only for the roulette.
+    pub fn bounce(
+        &mut self,
+        depths: &PathDepths,
+        lobe: Lobe,
+        attenuation: Color,
+        sampler: &mut dyn Sampler,
+    ) -> Bounce {
+        let count = match lobe {
+            Lobe::Diffuse => &mut self.diffuse,
+            Lobe::Specular => &mut self.specular,
+            Lobe::Transmission => &mut self.transmission,
+        };
+        if self.depth >= depths.max || depths.limit(lobe).is_some_and(|limit| *count >= limit) {
+            self.throughput = Color::new(0.0, 0.0, 0.0);
+            return Bounce::MaxDepth;
+        }
+        *count += 1;
+
+        let mut weight = attenuation;
+        if self.depth > depths.roulette {
+            // Paths survive as likely as they carry light, the survivors make up for the rest
+            let survival = (self.throughput * attenuation).max_component().min(1.0);
+            if sampler.get_1d() >= survival {
+                self.throughput = Color::new(0.0, 0.0, 0.0);
+                return Bounce::Roulette;
+            }
+            weight = attenuation * (1.0 / survival);
+        }
+
+        self.depth += 1;
+        self.throughput = self.throughput * weight;
+        Bounce::Continue { weight }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bounce, PathDepths, PathState};
+    use crate::{material::Lobe, sampler::SamplerKind, utils::color::Color};
+
+    #[test]
+    fn should_stop_at_the_depth_of_the_path_and_of_the_lobe() {
+        let mut sampler = SamplerKind::Independent.build(1, 0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let depths = PathDepths {
+            diffuse: Some(1),
+            ..PathDepths::new(4)
+        };
+
+        let mut path = PathState::default();
+        let mut bounce =
+            |path: &mut PathState, lobe| path.bounce(&depths, lobe, white, sampler.as_mut());
+        assert!(matches!(
+            bounce(&mut path, Lobe::Diffuse),
+            Bounce::Continue { .. }
+        ));
+        assert_eq!(bounce(&mut path.clone(), Lobe::Diffuse), Bounce::MaxDepth);
+        assert!(matches!(
+            bounce(&mut path, Lobe::Specular),
+            Bounce::Continue { .. }
+        ));
+        assert!(matches!(
+            bounce(&mut path, Lobe::Transmission),
+            Bounce::Continue { .. }
+        ));
+        assert_eq!(path.depth, 4);
+        assert_eq!(bounce(&mut path, Lobe::Specular), Bounce::MaxDepth);
+        assert_eq!(path.throughput, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_keep_the_light_of_the_paths_ended_by_the_roulette() {
+        let mut sampler = SamplerKind::Independent.build(1, 7);
+        let depths = PathDepths {
+            roulette: 0,
+            ..PathDepths::new(8)
+        };
+        let attenuation = Color::new(0.25, 0.1, 0.0);
+
+        let runs = 100_000;
+        let mut sum = 0.0;
+        for run in 0..runs {
+            sampler.start_pixel_sample((0, 0), run);
+            let mut path = PathState::default();
+            if let Bounce::Continue { weight } =
+                path.bounce(&depths, Lobe::Diffuse, attenuation, sampler.as_mut())
+            {
+                assert_eq!(weight.r, 1.0);
+                sum += weight.g;
+            }
+        }
+        // A quarter of the paths survive, carrying four times the light
+        let mean = sum / runs as f64;
+        assert!((mean - 0.1).abs() < 0.005, "{}", mean);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::vec3::Vec3;
 
+/// Half-line `origin + t * direction`, optionally at a point in time for motion blur.
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
@@ -23,10 +24,6 @@ impl Ray {
         }
     }
 
-    pub fn default() -> Self {
-        Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0))
-    }
-
     pub fn direction(&self) -> Vec3 {
         self.direction
     }
@@ -40,6 +37,12 @@ impl Ray {
     }
 }
 
+impl Default for Ray {
+    fn default() -> Self {
+        Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Ray;
@@ -1,51 +1,201 @@
-use std::sync::mpsc;
+use std::{
+    sync::{mpsc, Arc},
+    time::{Duration, Instant},
+};
 
 use crate::{
+    aov::{AovBuffers, AovSample},
     camera::Camera,
+    compare::luminance,
     config::Config,
+    debug::RenderMode,
+    denoise::Denoiser,
+    error::{Error, Result},
+    film::FilmTile,
     hitable::hitable_list::HitableList,
     hitable::Hitable,
-    material::ScatterRay,
-    ray::Ray,
-    thread_pool::ThreadPool,
-    utils::{color::Color, get_corrected_color, random_double, INFINITY},
+    integrator::{debug::DebugIntegrator, Integrator, IntegratorKind},
+    sampler::Sampler,
+    stats::{self, Counters, RenderStats},
+    thread_pool::{self, ThreadPool},
+    trace::{trace_path, TracedSample},
+    utils::{color::Color, get_corrected_color},
 };
-use image::ImageError;
 use indicatif::ProgressBar;
 
 #[derive(Debug)]
 struct RenderedPixels {
     range: (usize, usize),
-    pixels: Vec<Color>,
+    tiles: Vec<FilmTile>,
+    aovs: Vec<AovSample>,
+    counters: Counters,
+    busy: Duration,
+    worker: usize,
 }
 
+/// Renders a world through a camera into an image, with the integrator of its
+/// configuration.
 pub struct Renderer {
     config: Config,
+    integrator: Arc<dyn Integrator>,
     world: HitableList,
     cam: Camera,
     pixel_colours: Vec<Color>,
+    aovs: Option<AovBuffers>,
+    stats: RenderStats,
     pb: Option<ProgressBar>,
 }
 
 impl Renderer {
+    /// The world is put in a bounding volume hierarchy for the shutter interval of `cam`,
+    /// the integrator of the configuration computes the light unless it asks for a debug
+    /// view.
     pub fn new(config: Config, world: HitableList, cam: Camera, pb: Option<ProgressBar>) -> Self {
-        let mut pixel_colours: Vec<Color> =
+        let world = world.into_bvh(cam.time().unwrap_or((0.0, 0.0)));
+        let integrator: Arc<dyn Integrator> = match config.mode {
+            RenderMode::Path => config.integrator.build(&config, &world).into(),
+            mode => Arc::new(DebugIntegrator::new(mode, config.depths)),
+        };
+        let pixel_colours: Vec<Color> =
             vec![Color::new(0.0, 0.0, 0.0); config.image_height * config.image_width];
         Renderer {
             config,
+            integrator,
             world,
             cam,
             pixel_colours,
+            aovs: None,
+            stats: RenderStats::default(),
             pb,
         }
     }
 
-    fn save_image(
+    /// Replaces the camera, to render several views of the same world.
+    pub fn set_camera(&mut self, cam: Camera) {
+        self.cam = cam;
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Gamma corrected pixels of the last rendered frame, bottom row first.
+    pub fn frame(&self) -> &[Color] {
+        &self.pixel_colours
+    }
+
+    /// Pixels of the last rendered frame as they are saved, cropped to the region if the
+    /// configuration asks for it.
+    pub fn image(&self) -> Vec<Color> {
+        match self.config.region {
+            Some(region) if self.config.crop => region.crop(
+                &self.pixel_colours,
+                self.config.image_width,
+                self.config.image_height,
+            ),
+            _ => self.pixel_colours.clone(),
+        }
+    }
+
+    /// AOVs of the last rendered frame, cropped like `image`, if the configuration asks
+    /// for them.
+    pub fn aovs(&self) -> Option<&AovBuffers> {
+        self.aovs.as_ref()
+    }
+
+    /// Statistics of the frames rendered since the renderer was created or the statistics
+    /// were last taken.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    pub fn take_stats(&mut self) -> RenderStats {
+        std::mem::take(&mut self.stats)
+    }
+
+    // Adds the statistics of a frame that started at `start` with the process at `cpu_start`
+    // CPU time, `thread_busy` per rendering thread
+    fn record_stats(
+        &mut self,
+        start: Instant,
+        cpu_start: Option<Duration>,
+        counters: Counters,
+        thread_busy: Vec<Duration>,
+    ) {
+        let cpu_time = stats::process_cpu_time()
+            .zip(cpu_start)
+            .map(|(end, start)| end.saturating_sub(start));
+        let pixels = self.config.sample_window().area() as u64;
+        self.stats.merge(&RenderStats {
+            wall_time: start.elapsed(),
+            cpu_time,
+            counters,
+            samples_per_pixel: [(self.config.samples_per_pixel as usize, pixels)]
+                .into_iter()
+                .collect(),
+            peak_memory: stats::peak_memory(),
+            thread_busy,
+        });
+    }
+
+    // Puts the pixels of the rendered window in a frame filled with `fill` elsewhere
+    fn place_window<T: Clone>(config: &Config, pixels: Vec<T>, fill: T) -> Vec<T> {
+        let window = config.window();
+        if config.region.is_none() {
+            return pixels;
+        }
+
+        let mut frame = vec![fill; config.image_width * config.image_height];
+        for (k, value) in pixels.into_iter().enumerate() {
+            let (i, j) = window.pixel(k, config.image_height);
+            frame[j * config.image_width + i] = value;
+        }
+        frame
+    }
+
+    // Resolves the splatted samples in the window, black everywhere else, and keeps the
+    // AOVs of the window. The tiles come one per row, in the order of the rows.
+    fn store_window(&mut self, tiles: &[FilmTile], aovs: Vec<AovSample>) {
+        let (width, height) = (self.config.image_width, self.config.image_height);
+        let mut sums = vec![(Color::new(0.0, 0.0, 0.0), 0.0); width * height];
+        for tile in tiles {
+            tile.merge_into(&mut sums);
+        }
+
+        let window = self.config.window();
+        self.pixel_colours = sums
+            .into_iter()
+            .enumerate()
+            .map(|(k, (sum, weight))| {
+                if weight > 0.0 && window.contains((k % width, k / width), height) {
+                    // Negative filter lobes can leave slightly negative sums
+                    let sum = Color::new(sum.r.max(0.0), sum.g.max(0.0), sum.b.max(0.0));
+                    get_corrected_color(sum, weight)
+                } else {
+                    Color::new(0.0, 0.0, 0.0)
+                }
+            })
+            .collect();
+
+        self.aovs = self.config.aovs.then(|| {
+            let frame = Self::place_window(&self.config, aovs, AovSample::default());
+            let (width, height) = self.config.output_size();
+            let pixels = match self.config.region {
+                Some(region) if self.config.crop => {
+                    region.crop(&frame, self.config.image_width, self.config.image_height)
+                }
+                _ => frame,
+            };
+            AovBuffers::new(width, height, pixels)
+        });
+    }
+
+    pub(crate) fn save_image(
         pixel_colours: &[Color],
         width: usize,
         height: usize,
         file_path: &str,
-    ) -> Result<(), ImageError> {
+    ) -> Result<()> {
         let mut imgbuf = image::ImageBuffer::new(width as u32, height as u32);
 
         // Iterate over the coordinates and pixels of the image
@@ -54,96 +204,240 @@ impl Renderer {
             *pixel = image::Rgb([c.r as u8, c.g as u8, c.b as u8]);
         }
 
-        imgbuf.save(file_path)
+        imgbuf.save(file_path)?;
+        Ok(())
+    }
+
+    /// Saves the last rendered frame, the format is guessed from the extension.
+    pub fn save(&self, file_path: &str) -> Result<()> {
+        let (width, height) = self.config.output_size();
+        Self::save_image(&self.image(), width, height, file_path)
     }
 
-    pub fn save(&self, file_path: &str) -> Result<(), ImageError> {
-        Self::save_image(
-            &self.pixel_colours,
-            self.config.image_width,
-            self.config.image_height,
-            file_path,
-        )
+    /// Denoises the last rendered frame with its AOVs and saves it, the configuration
+    /// must ask for AOVs.
+    pub fn save_denoised(&self, denoiser: &Denoiser, file_path: &str) -> Result<()> {
+        let aovs = self.aovs.as_ref().ok_or_else(|| {
+            Error::InvalidConfig(String::from("denoising needs the AOVs of the frame"))
+        })?;
+        let pixels: Vec<Color> = denoiser
+            .denoise(aovs)
+            .into_iter()
+            .map(|c| get_corrected_color(c, 1.0))
+            .collect();
+        Self::save_image(&pixels, aovs.width, aovs.height, file_path)
     }
 
-    fn ray_color(r: &Ray, background: &Color, world: &dyn Hitable, depth: usize) -> Color {
-        if depth == 0 {
-            return *background;
+    /// Traces the samples of pixel `(x, y)`, from the top left corner, as a render with the
+    /// same configuration would and records every vertex of their paths. Only the paths of
+    /// the path integrator are traced.
+    pub fn trace_pixel(
+        &self,
+        background: &Color,
+        (x, y): (usize, usize),
+    ) -> Result<Vec<TracedSample>> {
+        self.config.validate()?;
+        if self.config.mode != RenderMode::Path || self.config.integrator != IntegratorKind::Path {
+            return Err(Error::InvalidConfig(String::from(
+                "only the paths of the path integrator can be traced",
+            )));
+        }
+        let (width, height) = (self.config.image_width, self.config.image_height);
+        if x >= width || y >= height {
+            return Err(Error::InvalidConfig(format!(
+                "pixel {},{} is outside of the {}x{} image",
+                x, y, width, height
+            )));
         }
 
-        // If hit something
-        if let Some(rec) = world.hit(r, 0.001, INFINITY) {
-            let mut emmited = rec.mat_ptr.emitted(rec.u, rec.v, &rec.p);
+        let (i, j) = (x, height - 1 - y);
+        let mut sampler = self
+            .config
+            .sampler
+            .build(self.config.samples_per_pixel as usize, self.config.seed);
+        let samples = (0..self.config.samples_per_pixel as usize)
+            .map(|sample| {
+                sampler.start_pixel_sample((i, j), sample);
+                let (du, dv) = sampler.get_2d();
+                let film = (i as f64 + du, j as f64 + dv);
+                let (u, v) = (film.0 / width as f64, film.1 / height as f64);
+                let (bounces, radiance) = match self.cam.get_ray(u, v, sampler.as_mut()) {
+                    Some(r) => trace_path(
+                        r,
+                        background,
+                        &self.world,
+                        &self.config.depths,
+                        sampler.as_mut(),
+                    ),
+                    None => (Vec::new(), Color::new(0.0, 0.0, 0.0)),
+                };
+                TracedSample {
+                    sample,
+                    film,
+                    bounces,
+                    radiance,
+                }
+            })
+            .collect();
+        Ok(samples)
+    }
 
-            if let Some(ScatterRay { ray, attenuation }) = rec.mat_ptr.scatter(r, &rec) {
-                return Self::ray_color(&ray, background, world, depth - 1) * attenuation + emmited;
-            } else {
-                return emmited;
+    // Splats the samples of pixel (i, j) on the tile, returns the average of their AOVs
+    // if the pixel is in the window and they are asked for
+    #[allow(clippy::too_many_arguments)]
+    fn render_pixel(
+        config: &Config,
+        integrator: &dyn Integrator,
+        world: &dyn Hitable,
+        cam: &Camera,
+        background: &Color,
+        sampler: &mut dyn Sampler,
+        (i, j): (usize, usize),
+        tile: &mut FilmTile,
+    ) -> Option<AovSample> {
+        let collect_aovs = config.aovs && config.window().contains((i, j), config.image_height);
+        let mut aovs: Option<AovSample> = None;
+
+        for s in 0..config.samples_per_pixel {
+            sampler.start_pixel_sample((i, j), s as usize);
+            let (du, dv) = sampler.get_2d();
+            let (x, y) = (i as f64 + du, j as f64 + dv);
+            let u = x / config.image_width as f64;
+            let v = y / config.image_height as f64;
+
+            let ray = cam.get_ray(u, v, sampler);
+            if ray.is_some() {
+                stats::count_camera_ray();
             }
+            let color = match ray {
+                // Points outside of the projection are black
+                None => {
+                    if collect_aovs {
+                        aovs.get_or_insert_with(AovSample::default);
+                    }
+                    Color::new(0.0, 0.0, 0.0)
+                }
+                Some(r) if collect_aovs => {
+                    let mut sample = AovSample::default();
+                    sample.beauty =
+                        integrator.li(&r, background, world, cam, sampler, Some(&mut sample));
+                    sample.variance = luminance(&sample.beauty).powi(2);
+                    match &mut aovs {
+                        Some(aovs) => aovs.accumulate(&sample),
+                        None => aovs = Some(sample),
+                    }
+                    sample.beauty
+                }
+                Some(r) => integrator.li(&r, background, world, cam, sampler, None),
+            };
+            tile.add_sample((x, y), color);
         }
 
-        return *background;
+        aovs.map(|aovs| aovs.average(config.samples_per_pixel as f64))
     }
 
-    pub fn render_current_frame(&mut self, background: &Color) {
-        // For updating the progress bar
-        let mut rendered = 0;
-
-        for j in (0..self.config.image_height).rev() {
-            for i in 0..self.config.image_width {
-                let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-
-                for _s in 0..self.config.samples_per_pixel {
-                    let u = (i as f64 + random_double(None)) / (self.config.image_width - 1) as f64;
-                    let v =
-                        (j as f64 + random_double(None)) / (self.config.image_height - 1) as f64;
-                    let r = self.cam.get_ray(u, v);
-                    pixel_color = pixel_color
-                        + Self::ray_color(&r, &background, &self.world, self.config.max_depth);
-                }
-
-                rendered += 1;
+    // Tile receiving the samples of the pixels in row `j`, a new one once the pixels move
+    // on to another row
+    fn row_tile<'a>(config: &Config, tiles: &'a mut Vec<FilmTile>, j: usize) -> &'a mut FilmTile {
+        if tiles.last().is_none_or(|tile| tile.source_row() != j) {
+            tiles.push(FilmTile::new(config, j));
+        }
+        tiles.last_mut().expect("a tile was just added")
+    }
 
-                match &mut self.pb {
-                    Some(pb) => {
-                        pb.set_position(rendered);
-                    }
-                    None => {}
-                }
+    /// Renders the frame (or its region) on the calling thread.
+    pub fn render_current_frame(&mut self, background: &Color) -> Result<()> {
+        self.config.validate()?;
+        let (start, cpu_start) = (Instant::now(), stats::process_cpu_time());
+        stats::take();
 
-                self.pixel_colours[j * self.config.image_width + i] =
-                    get_corrected_color(pixel_color, self.config.samples_per_pixel as f64);
+        // For updating the progress bar
+        let mut rendered = 0;
+        let mut sampler = self
+            .config
+            .sampler
+            .build(self.config.samples_per_pixel as usize, self.config.seed);
+
+        let window = self.config.sample_window();
+        let mut tiles = Vec::new();
+        let mut aovs = Vec::new();
+        for k in 0..window.area() {
+            let (i, j) = window.pixel(k, self.config.image_height);
+            let aov = Self::render_pixel(
+                &self.config,
+                self.integrator.as_ref(),
+                &self.world,
+                &self.cam,
+                background,
+                sampler.as_mut(),
+                (i, j),
+                Self::row_tile(&self.config, &mut tiles, j),
+            );
+
+            rendered += 1;
+
+            if let Some(pb) = &mut self.pb {
+                pb.set_position(rendered);
             }
+
+            aovs.extend(aov);
         }
+        self.store_window(&tiles, aovs);
+        self.record_stats(start, cpu_start, stats::take(), vec![start.elapsed()]);
+
+        Ok(())
     }
 
+    /// Renders the frame (or its region) split in `chunks` ranges of whole rows shared by
+    /// `threads` threads.
     pub fn render_current_frame_threadpool(
         &mut self,
         background: Color,
         threads: usize,
         chunks: usize,
-    ) {
+    ) -> Result<()> {
+        self.config.validate()?;
+        if threads == 0 || chunks == 0 {
+            return Err(Error::InvalidConfig(String::from(
+                "threads and chunks must be positive",
+            )));
+        }
+
+        let (start, cpu_start) = (Instant::now(), stats::process_cpu_time());
         let thread_pool = ThreadPool::new(threads);
         let (result_sender, result_receiver) = mpsc::channel::<RenderedPixels>();
 
-        let ranges = Renderer::get_ranges(
-            0,
-            self.config.image_height * self.config.image_width,
-            chunks,
-        );
+        // Chunks hold whole rows, each row is splatted on a tile of its own
+        let window = self.config.sample_window();
+        let ranges = Renderer::get_ranges(0, window.height(), chunks)
+            .into_iter()
+            .map(|(first, end)| (first * window.width(), end * window.width()));
         for range in ranges {
-            println!("{}, {}", range.0, range.1);
-
             let result_sender = result_sender.clone();
             let cloned_world = self.world.clone(); // This is an Arc clone actually not a deep clone
             let config = self.config.clone();
+            let integrator = self.integrator.clone();
             let cam = self.cam.clone();
-            let background = background.clone();
-            let bg = background.clone();
             thread_pool.execute(move || {
-                let pixels = Renderer::render_range(config, cloned_world, cam, bg, range);
+                let job_start = Instant::now();
+                stats::take();
+                let (tiles, aovs) = Renderer::render_range(
+                    config,
+                    integrator,
+                    cloned_world,
+                    cam,
+                    background,
+                    range,
+                );
                 result_sender
-                    .send(RenderedPixels { range, pixels })
+                    .send(RenderedPixels {
+                        range,
+                        tiles,
+                        aovs,
+                        counters: stats::take(),
+                        busy: job_start.elapsed(),
+                        worker: thread_pool::current_worker().unwrap_or(0),
+                    })
                     .unwrap();
             });
         }
@@ -159,17 +453,23 @@ impl Renderer {
             }
         }
 
-        // Now, 'results' contains all the RenderedPixels
-        println!("Received {} results", results.len());
-
+        // Merged in order, so the sums don't depend on which thread finished first
         Renderer::sort_rendered_pixels(&mut results);
 
-        self.pixel_colours.clear();
-
+        let mut tiles = Vec::new();
+        let mut aovs = Vec::new();
+        let mut counters = Counters::default();
+        let mut thread_busy = vec![Duration::ZERO; threads];
         for mut result in results {
-            println!("{:?}", result.range);
-            self.pixel_colours.append(&mut result.pixels);
+            tiles.append(&mut result.tiles);
+            aovs.append(&mut result.aovs);
+            counters.add(&result.counters);
+            thread_busy[result.worker] += result.busy;
         }
+        self.store_window(&tiles, aovs);
+        self.record_stats(start, cpu_start, counters, thread_busy);
+
+        Ok(())
     }
 
     fn get_ranges(start: usize, end: usize, chunks: usize) -> Vec<(usize, usize)> {
@@ -194,38 +494,218 @@ impl Renderer {
         ranges
     }
 
-    fn sort_rendered_pixels(rendered_pixels: &mut Vec<RenderedPixels>) {
-        rendered_pixels.sort_by(|a, b| a.range.cmp(&b.range));
+    fn sort_rendered_pixels(rendered_pixels: &mut [RenderedPixels]) {
+        rendered_pixels.sort_by_key(|a| a.range);
     }
 
     fn render_range(
         config: Config,
+        integrator: Arc<dyn Integrator>,
         world: impl Hitable,
         cam: Camera,
         background: Color,
         range: (usize, usize),
-    ) -> Vec<Color> {
-        assert!(range.1 - range.0 <= config.image_width * config.image_height);
-        let mut result = Vec::with_capacity(range.1 - range.0);
+    ) -> (Vec<FilmTile>, Vec<AovSample>) {
+        let window = config.sample_window();
+        assert!(range.1 <= window.area());
+        let mut tiles = Vec::new();
+        let mut aovs = Vec::new();
+        let mut sampler = config
+            .sampler
+            .build(config.samples_per_pixel as usize, config.seed);
 
         for k in range.0..range.1 {
-            let i = k % config.image_width;
-            let j = k / config.image_width;
+            // Pixels keep their image coordinates, so their seeds do not depend on the window
+            let (i, j) = window.pixel(k, config.image_height);
+            let aov = Self::render_pixel(
+                &config,
+                integrator.as_ref(),
+                &world,
+                &cam,
+                &background,
+                sampler.as_mut(),
+                (i, j),
+                Self::row_tile(&config, &mut tiles, j),
+            );
+            aovs.extend(aov);
+        }
+        (tiles, aovs)
+    }
+}
 
-            let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use crate::{
+        config::{Config, Region},
+        film::{Filter, FilterKind},
+        sampler::SamplerKind,
+        scene, Color,
+    };
+
+    fn render_with(threads: usize, chunks: usize, config: impl Fn(Config) -> Config) -> Renderer {
+        let scene = scene::checker_scene();
+        let config = config(Config::new(
+            scene.aspect_ratio,
+            24,
+            4,
+            8,
+            SamplerKind::Independent,
+            1234,
+        ));
+        let mut renderer = Renderer::new(config, scene.world, scene.camera, None);
+        renderer
+            .render_current_frame_threadpool(scene.background, threads, chunks)
+            .unwrap();
+        renderer
+    }
 
-            for _s in 0..config.samples_per_pixel {
-                let u = (i as f64 + random_double(None)) / (config.image_width - 1) as f64;
-                let v = (j as f64 + random_double(None)) / (config.image_height - 1) as f64;
-                let r = cam.get_ray(u, v);
-                pixel_color =
-                    pixel_color + Self::ray_color(&r, &background, &world, config.max_depth);
+    fn render(threads: usize, chunks: usize, sampler: SamplerKind) -> Renderer {
+        render_with(threads, chunks, |config| Config { sampler, ..config })
+    }
+
+    #[test]
+    fn should_render_identically_regardless_of_threads_and_chunks() {
+        for sampler in [SamplerKind::Independent, SamplerKind::Sobol] {
+            let single = render(1, 1, sampler);
+            let multi = render(3, 7, sampler);
+            assert_eq!(single.pixel_colours, multi.pixel_colours);
+        }
+    }
+
+    #[test]
+    fn should_render_region_like_the_full_frame() {
+        let full = render(1, 1, SamplerKind::Sobol);
+        let region = Region::new(5, 2, 17, 9);
+
+        let scene = scene::checker_scene();
+        let config = Config::new(scene.aspect_ratio, 24, 4, 8, SamplerKind::Sobol, 1234)
+            .with_region(region, false);
+        let mut renderer = Renderer::new(config.clone(), scene.world, scene.camera, None);
+        renderer
+            .render_current_frame_threadpool(scene.background, 2, 5)
+            .unwrap();
+
+        let (width, height) = (config.image_width, config.image_height);
+        let black = Color::new(0.0, 0.0, 0.0);
+        for j in 0..height {
+            for i in 0..width {
+                let y = height - 1 - j;
+                let inside = (5..17).contains(&i) && (2..9).contains(&y);
+                let expected = if inside {
+                    full.pixel_colours[j * width + i]
+                } else {
+                    black
+                };
+                assert_eq!(renderer.pixel_colours[j * width + i], expected);
+            }
+        }
+
+        renderer.config.crop = true;
+        assert_eq!(
+            renderer.image(),
+            region.crop(&full.pixel_colours, width, height)
+        );
+        assert_eq!(renderer.config.output_size(), (12, 7));
+    }
+
+    #[test]
+    fn should_split_beauty_into_aovs_without_changing_it() {
+        let scene = scene::cornell_box_scene();
+        let config = Config::new(1.0, 16, 4, 8, SamplerKind::Independent, 1234);
+        let mut without = Renderer::new(
+            config.clone(),
+            scene.world.clone(),
+            scene.camera.clone(),
+            None,
+        );
+        without.render_current_frame(&scene.background).unwrap();
+        assert!(without.aovs().is_none());
+
+        let mut with = Renderer::new(config.with_aovs(), scene.world, scene.camera, None);
+        with.render_current_frame_threadpool(scene.background, 2, 3)
+            .unwrap();
+        assert_eq!(with.pixel_colours, without.pixel_colours);
+
+        let aovs = with.aovs().unwrap();
+        assert_eq!(aovs.pixels.len(), 16 * 16);
+        for p in &aovs.pixels {
+            let sum = p.emission + p.direct + p.indirect;
+            for (a, b) in [
+                (sum.r, p.beauty.r),
+                (sum.g, p.beauty.g),
+                (sum.b, p.beauty.b),
+            ] {
+                assert!((a - b).abs() < 1e-9);
             }
-            result.push(get_corrected_color(
-                pixel_color,
-                config.samples_per_pixel as f64,
-            ));
         }
-        result
+
+        // Walls, light and boxes are told apart
+        let mut ids: Vec<usize> = aovs.pixels.iter().map(|p| p.object_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert!(ids.len() >= 5, "{:?}", ids);
+    }
+
+    #[test]
+    fn should_trace_the_paths_of_the_render() {
+        let scene = scene::cornell_box_scene();
+        let config = Config::new(1.0, 16, 8, 8, SamplerKind::Sobol, 1234);
+        let renderer = Renderer::new(config.clone(), scene.world, scene.camera, None);
+        let traced = renderer.trace_pixel(&scene.background, (9, 12)).unwrap();
+        assert_eq!(traced.len(), 8);
+
+        let mut sampler = config.sampler.build(8, config.seed);
+        for sample in &traced {
+            sampler.start_pixel_sample((9, 3), sample.sample);
+            let (du, dv) = sampler.get_2d();
+            assert_eq!(sample.film, (9.0 + du, 3.0 + dv));
+            let r = renderer
+                .cam
+                .get_ray(sample.film.0 / 16.0, sample.film.1 / 16.0, sampler.as_mut())
+                .unwrap();
+            let expected = renderer.integrator.li(
+                &r,
+                &scene.background,
+                &renderer.world,
+                &renderer.cam,
+                sampler.as_mut(),
+                None,
+            );
+            assert!((expected.r - sample.radiance.r).abs() < 1e-9);
+            assert!((expected.b - sample.radiance.b).abs() < 1e-9);
+
+            let last = sample.bounces.last().unwrap();
+            assert_eq!(last.radiance, sample.radiance);
+            assert!(sample.bounces.len() <= config.depths.max + 1);
+        }
+        assert!(renderer.trace_pixel(&scene.background, (16, 0)).is_err());
+    }
+
+    #[test]
+    fn should_splat_wide_filters_regardless_of_threads_and_chunks() {
+        let mitchell = |config: Config| config.with_filter(Filter::new(FilterKind::Mitchell, None));
+        let single = render_with(1, 1, mitchell);
+        // More chunks than rows leaves some of them empty
+        for (threads, chunks) in [(3, 7), (2, 50)] {
+            let multi = render_with(threads, chunks, mitchell);
+            assert_eq!(single.pixel_colours, multi.pixel_colours);
+        }
+    }
+
+    #[test]
+    fn should_render_filtered_region_like_the_full_frame() {
+        let gaussian = Filter::new(FilterKind::Gaussian, None);
+        let full = render_with(1, 1, |config| config.with_filter(gaussian));
+        let region = Region::new(5, 2, 17, 9);
+        let cropped = render_with(1, 1, |config| {
+            config.with_filter(gaussian).with_region(region, true)
+        });
+
+        let (width, height) = (full.config.image_width, full.config.image_height);
+        assert_eq!(
+            cropped.image(),
+            region.crop(&full.pixel_colours, width, height)
+        );
     }
 }
@@ -0,0 +1,194 @@
+use crate::{
+    camera::Camera,
+    error::Result,
+    renderer::Renderer,
+    utils::{color::Color, suffixed_path},
+    vec3::Vec3,
+};
+
+/// How the views of a rig are arranged in the output.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// All views in one image, left to right.
+    SideBySide,
+    /// All views in one image, top to bottom.
+    OverUnder,
+    /// One file per view, the view name is appended to the file name.
+    Separate,
+}
+
+/// Named cameras rendered one after the other from the same world.
+#[derive(Clone)]
+pub struct Rig {
+    views: Vec<(&'static str, Camera)>,
+    aspect_ratio: Option<f64>,
+}
+
+impl Rig {
+    /// Left and right eyes `interocular` apart, converging at `convergence`. The scene
+    /// camera's projection is kept, an equirectangular camera gives omnidirectional stereo.
+    pub fn stereo(camera: &Camera, interocular: f64, convergence: f64) -> Rig {
+        Rig {
+            views: vec![
+                (
+                    "left",
+                    camera.clone().with_eye(-interocular / 2.0, convergence),
+                ),
+                (
+                    "right",
+                    camera.clone().with_eye(interocular / 2.0, convergence),
+                ),
+            ],
+            aspect_ratio: None,
+        }
+    }
+
+    /// Six square 90° pinhole views around the camera origin, oriented with the camera.
+    pub fn cube_map(camera: &Camera) -> Rig {
+        let (u, v, w) = camera.basis();
+        let faces = [
+            ("right", u, v),
+            ("left", -u, v),
+            ("up", v, w),
+            ("down", -v, -w),
+            ("front", -w, v),
+            ("back", w, v),
+        ];
+
+        Rig {
+            views: faces
+                .iter()
+                .map(|&(name, direction, vup)| (name, Self::face(camera, direction, vup)))
+                .collect(),
+            aspect_ratio: Some(1.0),
+        }
+    }
+
+    fn face(camera: &Camera, direction: Vec3, vup: Vec3) -> Camera {
+        let origin = camera.origin();
+        Camera::new(
+            origin,
+            origin + direction,
+            vup,
+            90.0,
+            1.0,
+            0.0,
+            1.0,
+            camera.time(),
+        )
+    }
+
+    /// Aspect ratio the views must be rendered at, `None` to keep the scene's.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        self.aspect_ratio
+    }
+
+    pub fn views(&self) -> &[(&'static str, Camera)] {
+        &self.views
+    }
+
+    /// Renders every view with the renderer's world and settings.
+    pub fn render(
+        &self,
+        renderer: &mut Renderer,
+        background: Color,
+        threads: usize,
+        chunks: usize,
+    ) -> Result<RigFrames> {
+        let mut frames = Vec::with_capacity(self.views.len());
+        for (name, camera) in &self.views {
+            renderer.set_camera(camera.clone());
+            renderer.render_current_frame_threadpool(background, threads, chunks)?;
+            frames.push((*name, renderer.image()));
+        }
+
+        let (width, height) = renderer.config().output_size();
+        Ok(RigFrames {
+            width,
+            height,
+            frames,
+        })
+    }
+}
+
+/// Rendered views of a rig, rows are stored bottom first like `Renderer::image`.
+pub struct RigFrames {
+    pub width: usize,
+    pub height: usize,
+    pub frames: Vec<(&'static str, Vec<Color>)>,
+}
+
+impl RigFrames {
+    /// Saves the views, the format is guessed from the extension.
+    pub fn save(&self, file_path: &str, layout: Layout) -> Result<()> {
+        match layout {
+            Layout::Separate => {
+                for (name, pixels) in &self.frames {
+                    let path = suffixed_path(file_path, name);
+                    Renderer::save_image(pixels, self.width, self.height, &path)?;
+                }
+                Ok(())
+            }
+            Layout::SideBySide => {
+                let mut pixels = Vec::with_capacity(self.width * self.height * self.frames.len());
+                for row in 0..self.height {
+                    for (_, frame) in &self.frames {
+                        pixels.extend_from_slice(&frame[row * self.width..(row + 1) * self.width]);
+                    }
+                }
+                Renderer::save_image(
+                    &pixels,
+                    self.width * self.frames.len(),
+                    self.height,
+                    file_path,
+                )
+            }
+            Layout::OverUnder => {
+                // Bottom row first, so the last view goes first
+                let pixels: Vec<Color> = self
+                    .frames
+                    .iter()
+                    .rev()
+                    .flat_map(|(_, frame)| frame.iter().copied())
+                    .collect();
+                Renderer::save_image(
+                    &pixels,
+                    self.width,
+                    self.height * self.frames.len(),
+                    file_path,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rig;
+    use crate::{vec3::Vec3, Camera};
+
+    #[test]
+    fn should_look_along_every_axis_for_cube_maps() {
+        let camera = Camera::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(1.0, 2.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.5,
+            0.1,
+            3.0,
+            None,
+        );
+        let rig = Rig::cube_map(&camera);
+        assert_eq!(rig.views().len(), 6);
+        assert_eq!(rig.aspect_ratio(), Some(1.0));
+
+        let mut forward = Vec3::new(0.0, 0.0, 0.0);
+        for (_, face) in rig.views() {
+            assert_eq!(face.origin(), camera.origin());
+            let (_, _, w) = face.basis();
+            forward = forward + w;
+        }
+        assert!(forward.length() < 1e-9);
+    }
+}
@@ -0,0 +1,102 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::sampler::{pixel_hash, sample_rng, Sampler, ONE_MINUS_EPSILON};
+
+const PRIMES: [u64; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+/// Halton sequence sampler.
+///
+/// Dimension `d` uses the radical inverse in base of the `d`th prime. Every pixel
+/// rotates the sequence by its own random offset (Cranley-Patterson rotation) so
+/// neighbouring pixels don't share the same pattern. Dimensions past the prime table
+/// fall back to independent random samples.
+pub struct HaltonSampler {
+    pixel: (usize, usize),
+    sample_index: usize,
+    dimension: usize,
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        HaltonSampler {
+            pixel: (0, 0),
+            sample_index: 0,
+            dimension: 0,
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    fn sample_dimension(&mut self, dimension: usize) -> f64 {
+        if dimension >= PRIMES.len() {
+            return self.rng.gen_range(0.0..1.0);
+        }
+
+        let value = radical_inverse(PRIMES[dimension], self.sample_index as u64);
+        let offset =
+            (pixel_hash(self.seed, self.pixel, dimension) >> 11) as f64 / (1u64 << 53) as f64;
+        let rotated = value + offset;
+
+        f64::min(rotated - rotated.floor(), ONE_MINUS_EPSILON)
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), sample_index: usize) {
+        self.pixel = pixel;
+        self.sample_index = sample_index;
+        self.dimension = 0;
+        self.rng = sample_rng(self.seed, pixel, sample_index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+        self.sample_dimension(dimension)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let dimension = self.dimension;
+        self.dimension += 2;
+        (
+            self.sample_dimension(dimension),
+            self.sample_dimension(dimension + 1),
+        )
+    }
+}
+
+pub fn radical_inverse(base: u64, mut a: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_m = 1.0;
+    let mut reversed_digits = 0;
+
+    while a > 0 {
+        let next = a / base;
+        let digit = a - next * base;
+        reversed_digits = reversed_digits * base + digit;
+        inv_base_m *= inv_base;
+        a = next;
+    }
+
+    f64::min(reversed_digits as f64 * inv_base_m, ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::radical_inverse;
+
+    #[test]
+    fn should_compute_radical_inverse() {
+        assert_eq!(radical_inverse(2, 0), 0.0);
+        assert_eq!(radical_inverse(2, 1), 0.5);
+        assert_eq!(radical_inverse(2, 6), 0.375);
+        assert!((radical_inverse(3, 5) - 7.0 / 9.0).abs() < 1e-12);
+    }
+}
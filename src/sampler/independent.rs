@@ -0,0 +1,31 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::sampler::{sample_rng, Sampler};
+
+pub struct IndependentSampler {
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        IndependentSampler {
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), sample_index: usize) {
+        self.rng = sample_rng(self.seed, pixel, sample_index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.rng.gen_range(0.0..1.0)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.rng.gen_range(0.0..1.0), self.rng.gen_range(0.0..1.0))
+    }
+}
@@ -0,0 +1,149 @@
+pub mod halton;
+pub mod independent;
+pub mod sobol;
+pub mod stratified;
+
+use clap::ValueEnum;
+use rand::{rngs::SmallRng, SeedableRng};
+
+use crate::sampler::{
+    halton::HaltonSampler, independent::IndependentSampler, sobol::SobolSampler,
+    stratified::StratifiedSampler,
+};
+
+/// Source of the sample values used by the camera, materials and lights.
+///
+/// Every call to `get_1d` or `get_2d` consumes the next dimension(s) of the current
+/// sample, so the callers must always request them in the same order for the
+/// low-discrepancy samplers to be effective.
+pub trait Sampler: Send {
+    /// Prepare the sampler for sample number `sample_index` of `pixel`
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), sample_index: usize);
+    fn get_1d(&mut self) -> f64;
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SamplerKind {
+    /// Independent uniform random samples
+    Independent,
+    /// Jittered samples, one per stratum of every dimension
+    Stratified,
+    /// Halton sequence with a per pixel Cranley-Patterson rotation
+    Halton,
+    /// Owen scrambled Sobol sequence
+    Sobol,
+}
+
+impl SamplerKind {
+    pub fn build(&self, samples_per_pixel: usize, seed: u64) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler::new(seed)),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+// Largest f64 below one, sample values are always in [0, 1)
+pub const ONE_MINUS_EPSILON: f64 = 1.0 - f64::EPSILON / 2.0;
+
+pub fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5d329728ea185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81dadef4bc2dd44d);
+    v ^= v >> 33;
+    v
+}
+
+pub fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(0x9e3779b97f4a7c15, |h, v| mix_bits(h ^ mix_bits(*v)))
+}
+
+pub fn pixel_hash(seed: u64, pixel: (usize, usize), dimension: usize) -> u64 {
+    hash(&[seed, pixel.0 as u64, pixel.1 as u64, dimension as u64])
+}
+
+// Random generator for one sample of a pixel, it only depends on its arguments so the
+// result doesn't change with the thread or chunk that renders the pixel
+pub fn sample_rng(seed: u64, pixel: (usize, usize), sample_index: usize) -> SmallRng {
+    SmallRng::seed_from_u64(hash(&[
+        seed,
+        pixel.0 as u64,
+        pixel.1 as u64,
+        sample_index as u64,
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SamplerKind;
+
+    #[test]
+    fn should_return_values_in_unit_interval() {
+        for kind in [
+            SamplerKind::Independent,
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+        ] {
+            let mut sampler = kind.build(16, 0);
+            for s in 0..16 {
+                sampler.start_pixel_sample((3, 7), s);
+                for _ in 0..40 {
+                    let x = sampler.get_1d();
+                    let (y, z) = sampler.get_2d();
+                    assert!((0.0..1.0).contains(&x));
+                    assert!((0.0..1.0).contains(&y));
+                    assert!((0.0..1.0).contains(&z));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_cover_every_stratum_of_first_dimension() {
+        for kind in [
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+        ] {
+            let mut sampler = kind.build(16, 0);
+            let mut hits = [false; 16];
+            for s in 0..16 {
+                sampler.start_pixel_sample((1, 2), s);
+                let x = sampler.get_1d();
+                hits[(x * 16.0) as usize] = true;
+            }
+            assert!(hits.iter().all(|h| *h), "{:?} left a stratum empty", kind);
+        }
+    }
+
+    #[test]
+    fn should_repeat_samples_for_same_seed() {
+        for kind in [
+            SamplerKind::Independent,
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+        ] {
+            let mut first = kind.build(8, 42);
+            let mut second = kind.build(8, 42);
+
+            // Visit the samples in a different order
+            second.start_pixel_sample((9, 9), 1);
+            second.get_2d();
+
+            first.start_pixel_sample((5, 6), 3);
+            second.start_pixel_sample((5, 6), 3);
+            for _ in 0..100 {
+                assert_eq!(first.get_2d(), second.get_2d());
+                assert_eq!(first.get_1d(), second.get_1d());
+            }
+        }
+    }
+}
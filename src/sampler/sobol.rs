@@ -0,0 +1,120 @@
+use crate::sampler::{hash, pixel_hash, Sampler, ONE_MINUS_EPSILON};
+
+/// Owen scrambled Sobol sampler.
+///
+/// Uses the first two Sobol dimensions for every 1D or 2D request and decorrelates
+/// the requests by shuffling the sample index and scrambling the digits with a
+/// different seed per pixel and dimension, see Burley's "Practical Hash-based Owen
+/// Scrambling".
+pub struct SobolSampler {
+    pixel: (usize, usize),
+    sample_index: usize,
+    dimension: usize,
+    seed: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        SobolSampler {
+            pixel: (0, 0),
+            sample_index: 0,
+            dimension: 0,
+            seed,
+        }
+    }
+
+    fn scramble_seed(&self, dimension: usize, salt: u64) -> u32 {
+        hash(&[pixel_hash(self.seed, self.pixel, dimension), salt]) as u32
+    }
+
+    fn shuffled_index(&self) -> u32 {
+        nested_uniform_scramble(
+            self.sample_index as u32,
+            self.scramble_seed(self.dimension, 0),
+        )
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), sample_index: usize) {
+        self.pixel = pixel;
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let index = self.shuffled_index();
+        let x = nested_uniform_scramble(
+            sobol_dimension_0(index),
+            self.scramble_seed(self.dimension, 1),
+        );
+        self.dimension += 1;
+        to_unit_float(x)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let index = self.shuffled_index();
+        let x = nested_uniform_scramble(
+            sobol_dimension_0(index),
+            self.scramble_seed(self.dimension, 1),
+        );
+        let y = nested_uniform_scramble(
+            sobol_dimension_1(index),
+            self.scramble_seed(self.dimension, 2),
+        );
+        self.dimension += 2;
+        (to_unit_float(x), to_unit_float(y))
+    }
+}
+
+fn to_unit_float(x: u32) -> f64 {
+    f64::min(x as f64 / (1u64 << 32) as f64, ONE_MINUS_EPSILON)
+}
+
+// The first Sobol dimension is the base 2 van der Corput sequence
+fn sobol_dimension_0(index: u32) -> u32 {
+    index.reverse_bits()
+}
+
+fn sobol_dimension_1(mut index: u32) -> u32 {
+    let mut v: u32 = 1 << 31;
+    let mut result = 0;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= v;
+        }
+        index >>= 1;
+        v ^= v >> 1;
+    }
+    result
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
+
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sobol_dimension_0, sobol_dimension_1};
+
+    #[test]
+    fn should_generate_sobol_points() {
+        let half = 1 << 31;
+        let quarter = 1 << 30;
+        assert_eq!(sobol_dimension_0(1), half);
+        assert_eq!(sobol_dimension_1(1), half);
+        assert_eq!(sobol_dimension_0(2), quarter);
+        assert_eq!(sobol_dimension_1(2), half + quarter);
+        assert_eq!(sobol_dimension_0(3), half + quarter);
+        assert_eq!(sobol_dimension_1(3), quarter);
+    }
+}
@@ -0,0 +1,136 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::sampler::{pixel_hash, sample_rng, Sampler, ONE_MINUS_EPSILON};
+
+/// Jittered stratified sampler.
+///
+/// Every dimension is split into `samples_per_pixel` strata (a grid for 2D samples)
+/// and each sample of a pixel lands in a different one. The strata are visited in a
+/// different pseudo-random order for every dimension to avoid correlation between them.
+pub struct StratifiedSampler {
+    samples_per_pixel: usize,
+    x_strata: usize,
+    y_strata: usize,
+    pixel: (usize, usize),
+    sample_index: usize,
+    dimension: usize,
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: usize, seed: u64) -> Self {
+        let samples_per_pixel = samples_per_pixel.max(1);
+        let x_strata = (samples_per_pixel as f64).sqrt() as usize;
+        let y_strata = samples_per_pixel.div_ceil(x_strata);
+
+        StratifiedSampler {
+            samples_per_pixel,
+            x_strata,
+            y_strata,
+            pixel: (0, 0),
+            sample_index: 0,
+            dimension: 0,
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    // Stratum of the current sample for the current dimension among `count` strata
+    fn stratum(&self, count: usize) -> usize {
+        let seed = pixel_hash(self.seed, self.pixel, self.dimension);
+        permutation_element(self.sample_index % count, count, seed)
+    }
+
+    fn jitter(&mut self) -> f64 {
+        self.rng.gen_range(0.0..1.0)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), sample_index: usize) {
+        self.pixel = pixel;
+        self.sample_index = sample_index;
+        self.dimension = 0;
+        self.rng = sample_rng(self.seed, pixel, sample_index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let count = self.samples_per_pixel;
+        let stratum = self.stratum(count);
+        self.dimension += 1;
+
+        f64::min(
+            (stratum as f64 + self.jitter()) / count as f64,
+            ONE_MINUS_EPSILON,
+        )
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let stratum = self.stratum(self.x_strata * self.y_strata);
+        self.dimension += 2;
+
+        let x = (stratum % self.x_strata) as f64 + self.jitter();
+        let y = (stratum / self.x_strata) as f64 + self.jitter();
+        (
+            f64::min(x / self.x_strata as f64, ONE_MINUS_EPSILON),
+            f64::min(y / self.y_strata as f64, ONE_MINUS_EPSILON),
+        )
+    }
+}
+
+// Element `i` of a random permutation of [0, n) selected by `seed`, see Kensler's
+// "Correlated Multi-Jittered Sampling"
+fn permutation_element(mut i: usize, n: usize, seed: u64) -> usize {
+    let seed = seed as u32;
+    let l = n as u32;
+    let mut w = l.wrapping_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        let mut x = i as u32;
+        x ^= seed;
+        x = x.wrapping_mul(0xe170893d);
+        x ^= seed >> 16;
+        x ^= (x & w) >> 4;
+        x ^= seed >> 8;
+        x = x.wrapping_mul(0x0929eb3f);
+        x ^= seed >> 23;
+        x ^= (x & w) >> 1;
+        x = x.wrapping_mul(1 | seed >> 27);
+        x = x.wrapping_mul(0x6935fa69);
+        x ^= (x & w) >> 11;
+        x = x.wrapping_mul(0x74dcb303);
+        x ^= (x & w) >> 2;
+        x = x.wrapping_mul(0x9e501cc3);
+        x ^= (x & w) >> 2;
+        x = x.wrapping_mul(0xc860a3df);
+        x &= w;
+        x ^= x >> 5;
+
+        if x < l {
+            return (x.wrapping_add(seed) % l) as usize;
+        }
+        i = x as usize;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::permutation_element;
+
+    #[test]
+    fn should_permute_every_index_exactly_once() {
+        for n in [1, 2, 7, 16, 100] {
+            let mut seen = vec![false; n];
+            for i in 0..n {
+                seen[permutation_element(i, n, 0xdeadbeef)] = true;
+            }
+            assert!(seen.iter().all(|s| *s));
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
+use crate::animation::{CameraAnimation, Interpolation, Track, TransformTrack};
 use crate::camera::Camera;
+use crate::error::{Error, Result};
 use crate::hitable::hitable_list::HitableList;
 use crate::hitable::sphere::Sphere;
+use crate::hitable::Hitable;
 use crate::material::dielectric::Dielectric;
 use crate::material::diffuse_light::DiffuseLight;
 use crate::material::lambertian::Lambertian;
@@ -11,13 +14,297 @@ use crate::utils::color::Color;
 use crate::utils::{obj_import_as_triangles, random_double};
 use crate::vec3::Vec3;
 use crate::{hitable, texture};
+use hitable::animated::Animated;
 use hitable::moving_sphere::MovingSphere;
 use hitable::xy_rectangle::XYRectangle;
 use hitable::xz_rectangle::XZRectangle;
 use hitable::yz_rectangle::YZRectangle;
 use texture::checker_texture::CheckerTexture;
 
-pub fn random_scene() -> (HitableList, Camera, Color, f64) {
+/// Everything needed to render an image: the objects, the camera looking at them and
+/// the colour of rays escaping the scene.
+pub struct Scene {
+    pub world: HitableList,
+    pub camera: Camera,
+    pub background: Color,
+    pub aspect_ratio: f64,
+    /// Keyframed camera replacing `camera` when rendering animations.
+    pub camera_animation: Option<CameraAnimation>,
+}
+
+impl Scene {
+    pub fn new(world: HitableList, camera: Camera, background: Color, aspect_ratio: f64) -> Self {
+        Scene {
+            world,
+            camera,
+            background,
+            aspect_ratio,
+            camera_animation: None,
+        }
+    }
+
+    /// Camera at `time` with the given shutter interval, the static camera of the scene
+    /// is used when it has no camera animation.
+    pub fn camera_at(&self, time: f64, shutter: Option<(f64, f64)>) -> Camera {
+        match &self.camera_animation {
+            Some(animation) => animation.camera(time, self.aspect_ratio, shutter),
+            None => self.camera.clone().with_shutter(shutter),
+        }
+    }
+
+    /// Starts building a scene, see [`SceneBuilder`].
+    pub fn builder() -> SceneBuilder {
+        SceneBuilder::new()
+    }
+}
+
+/// Builds a [`Scene`] one object at a time.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use rtxon::{
+///     hitable::sphere::Sphere, material::lambertian::Lambertian, Camera, Color, Scene, Vec3,
+/// };
+///
+/// let aspect_ratio = 16.0 / 9.0;
+/// let look_from = Vec3::new(0.0, 0.5, 1.0);
+/// let look_at = Vec3::new(0.0, 0.0, -1.0);
+/// let camera = Camera::new(
+///     look_from,
+///     look_at,
+///     Vec3::new(0.0, 1.0, 0.0),
+///     60.0,
+///     aspect_ratio,
+///     0.0,
+///     (look_from - look_at).length(),
+///     None,
+/// );
+///
+/// let scene = Scene::builder()
+///     .camera(camera)
+///     .aspect_ratio(aspect_ratio)
+///     .background(Color::new(1.0, 1.0, 1.0))
+///     .add(Sphere::new(
+///         Vec3::new(0.0, 0.0, -1.0),
+///         0.5,
+///         Arc::new(Lambertian::new(Color::new(0.1, 0.2, 0.5))),
+///     ))
+///     .build()?;
+/// # Ok::<(), rtxon::Error>(())
+/// ```
+pub struct SceneBuilder {
+    world: HitableList,
+    camera: Option<Camera>,
+    camera_animation: Option<CameraAnimation>,
+    background: Color,
+    aspect_ratio: f64,
+}
+
+impl SceneBuilder {
+    pub fn new() -> Self {
+        SceneBuilder {
+            world: HitableList::new(),
+            camera: None,
+            camera_animation: None,
+            background: Color::new(0.0, 0.0, 0.0),
+            aspect_ratio: 16.0 / 9.0,
+        }
+    }
+
+    pub fn camera(mut self, camera: Camera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Keyframed camera used for animations, `camera` is still needed for still images.
+    pub fn camera_animation(mut self, camera_animation: CameraAnimation) -> Self {
+        self.camera_animation = Some(camera_animation);
+        self
+    }
+
+    /// Adds an object to the world, a [`HitableList`] can be added to add many at once.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<T: Hitable + 'static>(mut self, hitable: T) -> Self {
+        self.world.add(hitable);
+        self
+    }
+
+    pub fn background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Aspect ratio of the rendered image, it should match the one of the camera.
+    pub fn aspect_ratio(mut self, aspect_ratio: f64) -> Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Fails with [`Error::MissingCamera`] if no camera was given.
+    pub fn build(self) -> Result<Scene> {
+        Ok(Scene {
+            world: self.world,
+            camera: self.camera.ok_or(Error::MissingCamera)?,
+            background: self.background,
+            aspect_ratio: self.aspect_ratio,
+            camera_animation: self.camera_animation,
+        })
+    }
+}
+
+impl Default for SceneBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A built-in scene with its name and the settings it looks right with.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneInfo {
+    /// Name given to `--scene`, in kebab case.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Default image width in pixels.
+    pub width: usize,
+    /// Default samples per pixel.
+    pub samples: i32,
+    /// Width over height of the scene camera.
+    pub aspect_ratio: f64,
+    build: fn() -> Result<Scene>,
+}
+
+impl SceneInfo {
+    pub fn build(&self) -> Result<Scene> {
+        (self.build)()
+    }
+}
+
+/// Every built-in scene, in the order of their indices.
+pub const SCENES: [SceneInfo; 12] = [
+    SceneInfo {
+        name: "random-spheres",
+        description: "Hundreds of small random spheres around three large ones",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(random_scene()),
+    },
+    SceneInfo {
+        name: "random-moving-spheres",
+        description: "Random spheres bouncing during the shutter interval, for motion blur",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(random_moving_scene()),
+    },
+    SceneInfo {
+        name: "checker",
+        description: "Glass, diffuse and metal spheres on a checker ground",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(checker_scene()),
+    },
+    SceneInfo {
+        name: "checker-light",
+        description: "The checker scene lit by a small light in the sky",
+        width: 600,
+        samples: 256,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(checker_emmisive_material_scene()),
+    },
+    SceneInfo {
+        name: "depth-of-field",
+        description: "Diffuse, hollow glass and metal spheres seen from afar with a wide aperture",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(scene1()),
+    },
+    SceneInfo {
+        name: "three-spheres",
+        description: "Glass, diffuse and red metal spheres on a grey ground",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(scene2()),
+    },
+    SceneInfo {
+        name: "diffuse-spheres",
+        description: "White, blue and grey diffuse spheres seen with a wide field of view",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(scene3()),
+    },
+    SceneInfo {
+        name: "bokeh",
+        description: "Diffuse and metal spheres seen at an angle, blurred out of focus",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(scene4()),
+    },
+    SceneInfo {
+        name: "rectangle-light",
+        description: "Spheres lit by an emissive rectangle",
+        width: 600,
+        samples: 256,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(rectangular_light_scene()),
+    },
+    SceneInfo {
+        name: "cornell-box",
+        description: "Cornell box with a glass and a metal sphere under a ceiling light",
+        width: 600,
+        samples: 128,
+        aspect_ratio: 1.0,
+        build: || Ok(cornell_box_scene()),
+    },
+    SceneInfo {
+        name: "teapot",
+        description: "Utah teapot mesh loaded from models/teapot.obj, lit by a sphere",
+        width: 600,
+        samples: 128,
+        aspect_ratio: 16.0 / 9.0,
+        build: teapot_scene,
+    },
+    SceneInfo {
+        name: "animated",
+        description: "Bouncing ball and spinning panel seen by a keyframed camera, for --frames",
+        width: 600,
+        samples: 64,
+        aspect_ratio: 16.0 / 9.0,
+        build: || Ok(animated_scene()),
+    },
+];
+
+/// Built-in scene number `index`, in the order of [`SCENES`].
+pub fn by_index(index: usize) -> Result<Scene> {
+    match SCENES.get(index) {
+        Some(info) => info.build(),
+        None => Err(Error::SceneNotFound {
+            index,
+            count: SCENES.len(),
+        }),
+    }
+}
+
+/// Built-in scene called `name`, or numbered `name` when it is an index.
+pub fn find(name: &str) -> Result<&'static SceneInfo> {
+    if let Ok(index) = name.parse::<usize>() {
+        return SCENES.get(index).ok_or(Error::SceneNotFound {
+            index,
+            count: SCENES.len(),
+        });
+    }
+    SCENES
+        .iter()
+        .find(|info| info.name == name)
+        .ok_or_else(|| Error::UnknownScene(name.to_string()))
+}
+
+pub fn random_scene() -> Scene {
     let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
     let mut world = HitableList::new();
     world.add(Sphere::new(
@@ -89,10 +376,10 @@ pub fn random_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn random_moving_scene() -> (HitableList, Camera, Color, f64) {
+pub fn random_moving_scene() -> Scene {
     let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
     let mut world = HitableList::new();
     world.add(Sphere::new(
@@ -170,10 +457,10 @@ pub fn random_moving_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn checker_scene() -> (HitableList, Camera, Color, f64) {
+pub fn checker_scene() -> Scene {
     let mut world = HitableList::new();
 
     let checker_texture = Arc::new(CheckerTexture::new(
@@ -215,10 +502,10 @@ pub fn checker_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn checker_emmisive_material_scene() -> (HitableList, Camera, Color, f64) {
+pub fn checker_emmisive_material_scene() -> Scene {
     let mut world = HitableList::new();
 
     let checker_texture = Arc::new(CheckerTexture::new(
@@ -265,10 +552,10 @@ pub fn checker_emmisive_material_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(0.0, 0.0, 0.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn scene1() -> (HitableList, Camera, Color, f64) {
+pub fn scene1() -> Scene {
     let mut world = HitableList::new();
 
     let material_ground = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
@@ -312,10 +599,10 @@ pub fn scene1() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn scene2() -> (HitableList, Camera, Color, f64) {
+pub fn scene2() -> Scene {
     let mut world = HitableList::new();
 
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
@@ -352,16 +639,15 @@ pub fn scene2() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn scene3() -> (HitableList, Camera, Color, f64) {
+pub fn scene3() -> Scene {
     let mut world = HitableList::new();
 
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
     let material_center = Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0)));
     let material_left = Arc::new(Lambertian::new(Color::new(0.2, 0.3, 1.0)));
-    let material_right = Arc::new(Metal::new(Color::new(1.0, 0.2, 0.3), 0.1));
 
     world.add(Sphere::new(
         Vec3::new(0.0, -100.5, -1.0),
@@ -392,10 +678,10 @@ pub fn scene3() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn scene4() -> (HitableList, Camera, Color, f64) {
+pub fn scene4() -> Scene {
     let mut world = HitableList::new();
 
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
@@ -432,10 +718,10 @@ pub fn scene4() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(1.0, 1.0, 1.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn rectangular_light_scene() -> (HitableList, Camera, Color, f64) {
+pub fn rectangular_light_scene() -> Scene {
     let mut world = HitableList::new();
 
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
@@ -481,10 +767,10 @@ pub fn rectangular_light_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(0.0, 0.0, 0.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn cornell_box_scene() -> (HitableList, Camera, Color, f64) {
+pub fn cornell_box_scene() -> Scene {
     let mut world = HitableList::new();
 
     let glass = Arc::new(Dielectric::new(1.5));
@@ -545,24 +831,16 @@ pub fn cornell_box_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(0.0, 0.0, 0.0);
-    (world, cam, background, aspect_ratio)
+    Scene::new(world, cam, background, aspect_ratio)
 }
 
-pub fn teapot_scene() -> (HitableList, Camera, Color, f64) {
-    let mut world = HitableList::new();
-
+pub fn teapot_scene() -> Result<Scene> {
     let material_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-    let material_center = Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0)));
-    let material_left = Arc::new(Lambertian::new(Color::new(0.2, 0.3, 1.0)));
-    let material_right = Arc::new(Metal::new(Color::new(1.0, 0.2, 0.3), 0.1));
     let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
     let diffuse_light = Arc::new(DiffuseLight::new(Color::new(10.0, 10.0, 10.0)));
 
-    // world.add(Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5, material_center));
-    // world.add(Sphere::new(Vec3::new(-1.0, 0.0, -1.0), 0.5, material_left));
-
     // Add teapot triangles
-    world = obj_import_as_triangles("models/teapot.obj", white);
+    let mut world = obj_import_as_triangles("models/teapot.obj", white)?;
 
     world.add(Sphere::new(Vec3::new(-2.0, 5.0, -1.0), 0.5, diffuse_light));
 
@@ -592,5 +870,134 @@ pub fn teapot_scene() -> (HitableList, Camera, Color, f64) {
     );
 
     let background = Color::new(0.0, 0.0, 0.0);
-    (world, cam, background, aspect_ratio)
+    Ok(Scene::new(world, cam, background, aspect_ratio))
+}
+
+/// Two seconds of a bouncing ball and a spinning panel seen from an orbiting camera.
+pub fn animated_scene() -> Scene {
+    let mut world = HitableList::new();
+
+    let checker_texture = Arc::new(CheckerTexture::new(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    world.add(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(checker_texture)),
+    ));
+
+    // Falls, bounces and comes back to rest on its right
+    let bounce = Track::new(0.0, Vec3::new(-2.0, 3.0, 0.0), Interpolation::Linear)
+        .key(0.6, Vec3::new(-1.0, 0.5, 0.0), Interpolation::Bezier)
+        .key(1.3, Vec3::new(0.5, 2.0, 0.0), Interpolation::Bezier)
+        .key(2.0, Vec3::new(2.0, 0.5, 0.0), Interpolation::Linear);
+    world.add(Animated::new(
+        Sphere::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            0.5,
+            Arc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.1)),
+        ),
+        TransformTrack::new(
+            bounce,
+            Track::constant(Vec3::new(0.0, 0.0, 0.0)),
+            Track::constant(1.0),
+        ),
+    ));
+
+    let spin = Track::new(0.0, Vec3::new(0.0, 0.0, 0.0), Interpolation::Linear).key(
+        2.0,
+        Vec3::new(0.0, 360.0, 0.0),
+        Interpolation::Linear,
+    );
+    world.add(Animated::new(
+        XYRectangle::new(
+            (-0.75, 0.75),
+            (-0.75, 0.75),
+            0.0,
+            Arc::new(Lambertian::new(Color::new(0.7, 0.2, 0.2))),
+        ),
+        TransformTrack::new(
+            Track::constant(Vec3::new(0.0, 1.0, -2.0)),
+            spin,
+            Track::constant(1.0),
+        ),
+    ));
+
+    world.add(Sphere::new(
+        Vec3::new(0.0, 4.0, 0.0),
+        1.0,
+        Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0))),
+    ));
+
+    let aspect_ratio = 16.0 / 9.0;
+    let look_at = Vec3::new(0.0, 1.0, -0.5);
+    let look_from = Track::new(0.0, Vec3::new(-4.0, 2.0, 7.0), Interpolation::Bezier).key(
+        2.0,
+        Vec3::new(4.0, 3.0, 6.0),
+        Interpolation::Bezier,
+    );
+    let animation = CameraAnimation::new(
+        look_from,
+        Track::constant(look_at),
+        Track::constant(35.0),
+        Track::constant(0.05),
+    );
+    let cam = animation.camera(0.0, aspect_ratio, None);
+
+    let mut scene = Scene::new(world, cam, Color::new(0.5, 0.6, 0.8), aspect_ratio);
+    scene.camera_animation = Some(animation);
+    scene
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{by_index, find, Scene, SCENES};
+    use crate::error::Error;
+    use std::collections::HashSet;
+
+    #[test]
+    fn should_report_unknown_scene() {
+        assert!(matches!(
+            by_index(100),
+            Err(Error::SceneNotFound {
+                index: 100,
+                count: 12
+            })
+        ));
+    }
+
+    #[test]
+    fn should_find_scenes_by_name_or_index() {
+        assert_eq!(find("cornell-box").unwrap().name, "cornell-box");
+        assert_eq!(find("9").unwrap().name, "cornell-box");
+        assert!(matches!(
+            find("12"),
+            Err(Error::SceneNotFound { index: 12, .. })
+        ));
+        assert!(matches!(find("sponza"), Err(Error::UnknownScene(_))));
+
+        let names: HashSet<_> = SCENES.iter().map(|info| info.name).collect();
+        assert_eq!(names.len(), SCENES.len());
+    }
+
+    #[test]
+    fn should_register_the_aspect_ratio_of_the_scene() {
+        for info in SCENES.iter().filter(|info| info.name != "teapot") {
+            assert_eq!(
+                info.build().unwrap().aspect_ratio,
+                info.aspect_ratio,
+                "{}",
+                info.name
+            );
+        }
+    }
+
+    #[test]
+    fn should_require_camera() {
+        assert!(matches!(
+            Scene::builder().build(),
+            Err(Error::MissingCamera)
+        ));
+    }
 }
@@ -0,0 +1,449 @@
+//! Render settings gathered from the defaults of the scene, the config file and its
+//! preset, the scene file, the preset of the command line and the command line, each layer
+//! overriding the ones before it.
+
+use std::path::Path;
+
+use clap::ValueEnum;
+use toml::{Table, Value};
+
+use crate::{
+    error::{Error, Result},
+    path::{PathDepths, ROULETTE_DEPTH},
+    sampler::SamplerKind,
+    scene::{self, SceneInfo},
+};
+
+/// Config file read from the working directory when no other one is given.
+pub const CONFIG_FILE: &str = "rtxon.toml";
+
+/// Samples, depth and denoising picked together for a quality level.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// A few denoised samples to check framing and materials, in seconds
+    Preview,
+    /// Enough denoised samples to judge the lighting
+    Draft,
+    /// Converged render without denoising
+    Final,
+}
+
+impl Preset {
+    pub fn settings(&self) -> Settings {
+        let (samples, max_depth, denoise) = match self {
+            Preset::Preview => (4, 4, true),
+            Preset::Draft => (32, 16, true),
+            Preset::Final => (1024, 100, false),
+        };
+        Settings {
+            samples: Some(samples),
+            max_depth: Some(max_depth),
+            denoise: Some(denoise),
+            ..Default::default()
+        }
+    }
+}
+
+/// Settings one layer sets, `None` leaves them to the layers below.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Settings {
+    pub width: Option<usize>,
+    pub samples: Option<i32>,
+    pub max_depth: Option<usize>,
+    pub diffuse_depth: Option<usize>,
+    pub specular_depth: Option<usize>,
+    pub transmission_depth: Option<usize>,
+    pub roulette_depth: Option<usize>,
+    pub threads: Option<usize>,
+    pub chunks: Option<usize>,
+    pub sampler: Option<SamplerKind>,
+    pub denoise: Option<bool>,
+}
+
+impl Settings {
+    /// Width and samples a built-in scene looks right with.
+    pub fn scene_defaults(info: &SceneInfo) -> Self {
+        Settings {
+            width: Some(info.width),
+            samples: Some(info.samples),
+            ..Default::default()
+        }
+    }
+
+    /// These settings with the ones `other` sets replaced.
+    pub fn overridden_by(&self, other: &Settings) -> Settings {
+        Settings {
+            width: other.width.or(self.width),
+            samples: other.samples.or(self.samples),
+            max_depth: other.max_depth.or(self.max_depth),
+            diffuse_depth: other.diffuse_depth.or(self.diffuse_depth),
+            specular_depth: other.specular_depth.or(self.specular_depth),
+            transmission_depth: other.transmission_depth.or(self.transmission_depth),
+            roulette_depth: other.roulette_depth.or(self.roulette_depth),
+            threads: other.threads.or(self.threads),
+            chunks: other.chunks.or(self.chunks),
+            sampler: other.sampler.or(self.sampler),
+            denoise: other.denoise.or(self.denoise),
+        }
+    }
+
+    /// Settings left unset take their built-in default.
+    pub fn resolve(&self) -> RenderSettings {
+        let defaults = RenderSettings::default();
+        RenderSettings {
+            width: self.width.unwrap_or(defaults.width),
+            samples: self.samples.unwrap_or(defaults.samples),
+            max_depth: self.max_depth.unwrap_or(defaults.max_depth),
+            diffuse_depth: self.diffuse_depth.or(defaults.diffuse_depth),
+            specular_depth: self.specular_depth.or(defaults.specular_depth),
+            transmission_depth: self.transmission_depth.or(defaults.transmission_depth),
+            roulette_depth: self.roulette_depth.unwrap_or(defaults.roulette_depth),
+            threads: self.threads.unwrap_or(defaults.threads),
+            chunks: self.chunks.unwrap_or(defaults.chunks),
+            sampler: self.sampler.unwrap_or(defaults.sampler),
+            denoise: self.denoise.unwrap_or(defaults.denoise),
+        }
+    }
+
+    // Reads the settings keys of `table`, `source` names it in errors
+    fn from_table(table: &Table, source: &str, other_keys: &[&str]) -> Result<Self> {
+        let invalid = |key: &str, expected: &str| {
+            Error::InvalidConfig(format!("{}: {} must be {}", source, key, expected))
+        };
+        let count = |key: &str| -> Result<Option<i64>> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(Value::Integer(n)) if *n > 0 => Ok(Some(*n)),
+                Some(_) => Err(invalid(key, "a positive integer")),
+            }
+        };
+        // Depths of a kind of bounce may be zero, to leave that kind out
+        let depth = |key: &str| -> Result<Option<usize>> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(Value::Integer(n)) if *n >= 0 => Ok(Some(*n as usize)),
+                Some(_) => Err(invalid(key, "a non-negative integer")),
+            }
+        };
+
+        if let Some(key) = table.keys().find(|key| {
+            !SETTINGS_KEYS.contains(&key.as_str()) && !other_keys.contains(&key.as_str())
+        }) {
+            return Err(Error::InvalidConfig(format!(
+                "{}: unknown setting {}",
+                source, key
+            )));
+        }
+
+        let sampler = match table.get("sampler") {
+            None => None,
+            Some(Value::String(name)) => Some(
+                SamplerKind::from_str(name, true)
+                    .map_err(|_| invalid("sampler", "a sampler name"))?,
+            ),
+            Some(_) => return Err(invalid("sampler", "a sampler name")),
+        };
+        let denoise = match table.get("denoise") {
+            None => None,
+            Some(Value::Boolean(denoise)) => Some(*denoise),
+            Some(_) => return Err(invalid("denoise", "true or false")),
+        };
+        Ok(Settings {
+            width: count("width")?.map(|n| n as usize),
+            samples: count("samples")?
+                .map(i32::try_from)
+                .transpose()
+                .map_err(|_| invalid("samples", "a smaller integer"))?,
+            max_depth: count("max_depth")?.map(|n| n as usize),
+            diffuse_depth: depth("diffuse_depth")?,
+            specular_depth: depth("specular_depth")?,
+            transmission_depth: depth("transmission_depth")?,
+            roulette_depth: depth("roulette_depth")?,
+            threads: count("threads")?.map(|n| n as usize),
+            chunks: count("chunks")?.map(|n| n as usize),
+            sampler,
+            denoise,
+        })
+    }
+}
+
+const SETTINGS_KEYS: [&str; 11] = [
+    "width",
+    "samples",
+    "max_depth",
+    "diffuse_depth",
+    "specular_depth",
+    "transmission_depth",
+    "roulette_depth",
+    "threads",
+    "chunks",
+    "sampler",
+    "denoise",
+];
+
+/// Every setting of a render, once the layers are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub samples: i32,
+    pub max_depth: usize,
+    pub diffuse_depth: Option<usize>,
+    pub specular_depth: Option<usize>,
+    pub transmission_depth: Option<usize>,
+    pub roulette_depth: usize,
+    pub threads: usize,
+    pub chunks: usize,
+    pub sampler: SamplerKind,
+    pub denoise: bool,
+}
+
+impl RenderSettings {
+    pub fn depths(&self) -> PathDepths {
+        PathDepths {
+            max: self.max_depth,
+            diffuse: self.diffuse_depth,
+            specular: self.specular_depth,
+            transmission: self.transmission_depth,
+            roulette: self.roulette_depth,
+        }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 600,
+            samples: 128,
+            max_depth: 100,
+            diffuse_depth: None,
+            specular_depth: None,
+            transmission_depth: None,
+            roulette_depth: ROULETTE_DEPTH,
+            threads: 1,
+            chunks: 1,
+            sampler: SamplerKind::Independent,
+            denoise: false,
+        }
+    }
+}
+
+/// Defaults of every render, the settings at the top level of `rtxon.toml` over those of
+/// its preset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfigFile {
+    pub preset: Option<Preset>,
+    pub settings: Settings,
+}
+
+impl ConfigFile {
+    pub fn parse(text: &str, source: &str) -> Result<Self> {
+        let table = parse_table(text, source)?;
+        let preset = match table.get("preset") {
+            None => None,
+            Some(Value::String(name)) => Some(Preset::from_str(name, true).map_err(|_| {
+                Error::InvalidConfig(format!("{}: unknown preset {}", source, name))
+            })?),
+            Some(_) => {
+                return Err(Error::InvalidConfig(format!(
+                    "{}: preset must be a preset name",
+                    source
+                )))
+            }
+        };
+        Ok(ConfigFile {
+            preset,
+            settings: Settings::from_table(&table, source, &["preset"])?,
+        })
+    }
+
+    /// Settings of the file, with those of its preset under them.
+    pub fn defaults(&self) -> Settings {
+        self.preset
+            .map(|preset| preset.settings())
+            .unwrap_or_default()
+            .overridden_by(&self.settings)
+    }
+
+    pub fn load(file_path: &str) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(file_path)?, file_path)
+    }
+
+    /// `file_path` when given, otherwise `rtxon.toml` if there is one, otherwise no
+    /// defaults at all.
+    pub fn find(file_path: Option<&str>) -> Result<Self> {
+        match file_path {
+            Some(path) => Self::load(path),
+            None if Path::new(CONFIG_FILE).is_file() => Self::load(CONFIG_FILE),
+            None => Ok(ConfigFile::default()),
+        }
+    }
+}
+
+/// Built-in scene with render settings of its own, read from a TOML file:
+///
+/// ```toml
+/// scene = "cornell-box"
+///
+/// [render]
+/// width = 1024
+/// samples = 4096
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SceneFile {
+    pub scene: &'static SceneInfo,
+    /// The `[render]` table, nothing for a scene given by name.
+    pub settings: Settings,
+}
+
+impl SceneFile {
+    pub fn parse(text: &str, source: &str) -> Result<Self> {
+        let table = parse_table(text, source)?;
+        if let Some(key) = table.keys().find(|key| *key != "scene" && *key != "render") {
+            return Err(Error::InvalidConfig(format!(
+                "{}: unknown key {}",
+                source, key
+            )));
+        }
+
+        let scene = match table.get("scene") {
+            Some(Value::String(name)) => scene::find(name)?,
+            _ => {
+                return Err(Error::InvalidConfig(format!(
+                    "{}: scene must name a built-in scene",
+                    source
+                )))
+            }
+        };
+        let render = match table.get("render") {
+            None => Settings::default(),
+            Some(Value::Table(render)) => Settings::from_table(render, source, &[])?,
+            Some(_) => {
+                return Err(Error::InvalidConfig(format!(
+                    "{}: render must be a table",
+                    source
+                )))
+            }
+        };
+        Ok(SceneFile {
+            scene,
+            settings: render,
+        })
+    }
+
+    pub fn load(file_path: &str) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(file_path)?, file_path)
+    }
+
+    /// The defaults of the scene overridden by `config`, then by the `[render]` table.
+    pub fn settings_over(&self, config: &Settings) -> Settings {
+        Settings::scene_defaults(self.scene)
+            .overridden_by(config)
+            .overridden_by(&self.settings)
+    }
+}
+
+fn parse_table(text: &str, source: &str) -> Result<Table> {
+    text.parse::<Table>()
+        .map_err(|e| Error::InvalidConfig(format!("{}: {}", source, e.message())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigFile, Preset, SceneFile, Settings};
+    use crate::{error::Error, sampler::SamplerKind, scene};
+
+    #[test]
+    fn should_read_defaults_from_the_config_file() {
+        let config = ConfigFile::parse(
+            "preset = \"draft\"\nthreads = 8\nsampler = \"sobol\"\n",
+            "rtxon.toml",
+        )
+        .unwrap();
+        assert_eq!(config.preset, Some(Preset::Draft));
+        assert_eq!(config.settings.threads, Some(8));
+        assert_eq!(config.settings.sampler, Some(SamplerKind::Sobol));
+
+        assert!(matches!(
+            ConfigFile::parse("treads = 8", "rtxon.toml"),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            ConfigFile::parse("samples = -1", "rtxon.toml"),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn should_override_the_lower_layers() {
+        let scene = SceneFile::parse(
+            "scene = \"cornell-box\"\n[render]\nsamples = 4096\nmax_depth = 50\ndiffuse_depth = 0\n",
+            "box.toml",
+        )
+        .unwrap();
+        assert_eq!(scene.scene.name, "cornell-box");
+        assert_eq!(scene.settings.width, None);
+
+        let config = Settings {
+            threads: Some(8),
+            max_depth: Some(20),
+            ..Default::default()
+        };
+        let command_line = Settings {
+            samples: Some(16),
+            ..Default::default()
+        };
+        let settings = scene
+            .settings_over(&config)
+            .overridden_by(&Preset::Draft.settings())
+            .overridden_by(&command_line)
+            .resolve();
+        assert_eq!(settings.width, 600);
+        assert_eq!(settings.samples, 16);
+        assert_eq!(settings.max_depth, 16);
+        assert_eq!(settings.threads, 8);
+        assert!(settings.denoise);
+        assert_eq!(settings.chunks, 1);
+        assert_eq!(settings.depths().diffuse, Some(0));
+        assert_eq!(settings.depths().specular, None);
+    }
+
+    #[test]
+    fn should_override_the_scene_defaults_with_the_config_file() {
+        let config = ConfigFile::parse("width = 1024\nsamples = 64\n", "rtxon.toml").unwrap();
+        let scene = SceneFile {
+            scene: scene::find("cornell-box").unwrap(),
+            settings: Settings::default(),
+        };
+        let settings = scene.settings_over(&config.settings).resolve();
+        assert_eq!(settings.width, 1024);
+        assert_eq!(settings.samples, 64);
+
+        let scene = SceneFile::parse(
+            "scene = \"cornell-box\"\n[render]\nsamples = 4096\n",
+            "box.toml",
+        )
+        .unwrap();
+        let settings = scene.settings_over(&config.settings).resolve();
+        assert_eq!(settings.width, 1024);
+        assert_eq!(settings.samples, 4096);
+    }
+
+    #[test]
+    fn should_override_the_config_preset_with_the_scene_file() {
+        let config =
+            ConfigFile::parse("preset = \"preview\"\nmax_depth = 8\n", "rtxon.toml").unwrap();
+        let defaults = config.defaults();
+        assert_eq!(defaults.samples, Some(4));
+        assert_eq!(defaults.max_depth, Some(8));
+
+        let scene = SceneFile::parse(
+            "scene = \"cornell-box\"\n[render]\nsamples = 4096\n",
+            "box.toml",
+        )
+        .unwrap();
+        let settings = scene.settings_over(&defaults).resolve();
+        assert_eq!(settings.samples, 4096);
+        assert_eq!(settings.max_depth, 8);
+        assert!(settings.denoise);
+    }
+}
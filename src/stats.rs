@@ -0,0 +1,366 @@
+//! Counters of the work done while tracing rays, kept per thread so the hot paths only
+//! touch thread local cells, and the statistics of whole renders built from them.
+
+use std::{cell::Cell, collections::BTreeMap, fmt::Write, time::Duration};
+
+use serde_json::{json, Value};
+
+use crate::error::Result;
+
+thread_local! {
+    static COUNTERS: Cell<Counters> = const { Cell::new(Counters::zero()) };
+}
+
+/// Rays and intersection tests counted while rendering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counters {
+    pub camera_rays: u64,
+    /// Rays leaving a surface after a scattering event.
+    pub bounce_rays: u64,
+    /// Visibility rays toward lights, none until an integrator samples them.
+    pub shadow_rays: u64,
+    /// BVH nodes whose bounding box was tested.
+    pub bvh_nodes: u64,
+    /// Spheres, triangles and rectangles tested.
+    pub primitive_tests: u64,
+}
+
+impl Counters {
+    const fn zero() -> Self {
+        Counters {
+            camera_rays: 0,
+            bounce_rays: 0,
+            shadow_rays: 0,
+            bvh_nodes: 0,
+            primitive_tests: 0,
+        }
+    }
+
+    pub fn rays(&self) -> u64 {
+        self.camera_rays + self.bounce_rays + self.shadow_rays
+    }
+
+    /// BVH nodes and primitives tested.
+    pub fn traversal(&self) -> u64 {
+        self.bvh_nodes + self.primitive_tests
+    }
+
+    pub fn add(&mut self, other: &Counters) {
+        self.camera_rays += other.camera_rays;
+        self.bounce_rays += other.bounce_rays;
+        self.shadow_rays += other.shadow_rays;
+        self.bvh_nodes += other.bvh_nodes;
+        self.primitive_tests += other.primitive_tests;
+    }
+}
+
+#[inline]
+fn count(f: impl FnOnce(&mut Counters)) {
+    COUNTERS.with(|c| {
+        let mut counters = c.get();
+        f(&mut counters);
+        c.set(counters);
+    });
+}
+
+#[inline]
+pub fn count_camera_ray() {
+    count(|c| c.camera_rays += 1);
+}
+
+#[inline]
+pub fn count_bounce_ray() {
+    count(|c| c.bounce_rays += 1);
+}
+
+#[inline]
+pub fn count_shadow_ray() {
+    count(|c| c.shadow_rays += 1);
+}
+
+#[inline]
+pub fn count_bvh_node() {
+    count(|c| c.bvh_nodes += 1);
+}
+
+#[inline]
+pub fn count_primitive_test() {
+    count(|c| c.primitive_tests += 1);
+}
+
+/// Work counted on this thread so far.
+pub fn current() -> Counters {
+    COUNTERS.with(|c| c.get())
+}
+
+/// Work counted on this thread since the last call, the counters start again from zero.
+pub fn take() -> Counters {
+    COUNTERS.with(|c| c.replace(Counters::zero()))
+}
+
+/// CPU time used by the process so far, user and system.
+#[cfg(unix)]
+pub fn process_cpu_time() -> Option<Duration> {
+    let usage = resource_usage()?;
+    let time = |t: libc::timeval| Duration::new(t.tv_sec as u64, t.tv_usec as u32 * 1000);
+    Some(time(usage.ru_utime) + time(usage.ru_stime))
+}
+
+#[cfg(not(unix))]
+pub fn process_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// Largest resident memory of the process so far, in bytes.
+#[cfg(unix)]
+pub fn peak_memory() -> Option<u64> {
+    let max_rss = resource_usage()?.ru_maxrss as u64;
+    // Linux reports kilobytes, macOS bytes
+    if cfg!(target_os = "macos") {
+        Some(max_rss)
+    } else {
+        Some(max_rss * 1024)
+    }
+}
+
+#[cfg(not(unix))]
+pub fn peak_memory() -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn resource_usage() -> Option<libc::rusage> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage only writes the struct it is given
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } == 0 {
+        // SAFETY: initialised by the successful call
+        Some(unsafe { usage.assume_init() })
+    } else {
+        None
+    }
+}
+
+/// Statistics of one or more renders.
+#[derive(Debug, Clone, Default)]
+pub struct RenderStats {
+    pub wall_time: Duration,
+    /// CPU time of the whole process while rendering, when the platform reports it.
+    pub cpu_time: Option<Duration>,
+    pub counters: Counters,
+    /// Number of pixels for each number of samples taken.
+    pub samples_per_pixel: BTreeMap<usize, u64>,
+    /// Largest resident memory of the process, in bytes.
+    pub peak_memory: Option<u64>,
+    /// Time every thread spent rendering, the calling thread or the pool workers.
+    pub thread_busy: Vec<Duration>,
+}
+
+impl RenderStats {
+    /// Adds the statistics of another render, as if both were one longer render.
+    pub fn merge(&mut self, other: &RenderStats) {
+        self.wall_time += other.wall_time;
+        self.cpu_time = match (self.cpu_time, other.cpu_time) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.counters.add(&other.counters);
+        for (samples, pixels) in &other.samples_per_pixel {
+            *self.samples_per_pixel.entry(*samples).or_insert(0) += pixels;
+        }
+        self.peak_memory = self.peak_memory.max(other.peak_memory);
+        if self.thread_busy.len() < other.thread_busy.len() {
+            self.thread_busy
+                .resize(other.thread_busy.len(), Duration::ZERO);
+        }
+        for (busy, other) in self.thread_busy.iter_mut().zip(&other.thread_busy) {
+            *busy += *other;
+        }
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        let wall = self.wall_time.as_secs_f64();
+        if wall > 0.0 {
+            self.counters.rays() as f64 / wall
+        } else {
+            0.0
+        }
+    }
+
+    /// Rays per camera path, the camera ray and its bounces.
+    pub fn average_path_length(&self) -> f64 {
+        let c = &self.counters;
+        ratio(c.camera_rays + c.bounce_rays, c.camera_rays)
+    }
+
+    pub fn nodes_per_ray(&self) -> f64 {
+        ratio(self.counters.bvh_nodes, self.counters.rays())
+    }
+
+    pub fn primitive_tests_per_ray(&self) -> f64 {
+        ratio(self.counters.primitive_tests, self.counters.rays())
+    }
+
+    /// Fraction of the wall time every thread spent rendering.
+    pub fn thread_utilisation(&self) -> Vec<f64> {
+        let wall = self.wall_time.as_secs_f64();
+        self.thread_busy
+            .iter()
+            .map(|busy| {
+                if wall > 0.0 {
+                    busy.as_secs_f64() / wall
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    /// Human readable report.
+    pub fn summary(&self) -> String {
+        let c = &self.counters;
+        let mut s = String::new();
+        writeln!(s, "Render statistics").unwrap();
+        write!(
+            s,
+            "  Time:           {:.3} s wall",
+            self.wall_time.as_secs_f64()
+        )
+        .unwrap();
+        if let Some(cpu) = self.cpu_time {
+            write!(s, ", {:.3} s CPU", cpu.as_secs_f64()).unwrap();
+        }
+        writeln!(s).unwrap();
+        writeln!(
+            s,
+            "  Rays:           {} ({} camera, {} bounce, {} shadow)",
+            c.rays(),
+            c.camera_rays,
+            c.bounce_rays,
+            c.shadow_rays
+        )
+        .unwrap();
+        writeln!(s, "  Rays/s:         {:.3} M", self.rays_per_second() / 1e6).unwrap();
+        writeln!(
+            s,
+            "  Path length:    {:.3} rays",
+            self.average_path_length()
+        )
+        .unwrap();
+        writeln!(
+            s,
+            "  Per ray:        {:.2} BVH nodes, {:.2} primitive tests",
+            self.nodes_per_ray(),
+            self.primitive_tests_per_ray()
+        )
+        .unwrap();
+        let spp: Vec<String> = self
+            .samples_per_pixel
+            .iter()
+            .map(|(samples, pixels)| format!("{} px x {} spp", pixels, samples))
+            .collect();
+        writeln!(s, "  Samples:        {}", spp.join(", ")).unwrap();
+        if let Some(bytes) = self.peak_memory {
+            writeln!(
+                s,
+                "  Peak memory:    {:.1} MiB",
+                bytes as f64 / (1 << 20) as f64
+            )
+            .unwrap();
+        }
+        let utilisation: Vec<String> = self
+            .thread_utilisation()
+            .iter()
+            .map(|u| format!("{:.0}%", u * 100.0))
+            .collect();
+        write!(s, "  Threads busy:   {}", utilisation.join(" ")).unwrap();
+        s
+    }
+
+    /// Statistics and the rates derived from them, times in seconds and memory in bytes.
+    pub fn to_json(&self) -> Value {
+        let c = &self.counters;
+        json!({
+            "wall_time": self.wall_time.as_secs_f64(),
+            "cpu_time": self.cpu_time.map(|t| t.as_secs_f64()),
+            "rays": {
+                "total": c.rays(),
+                "camera": c.camera_rays,
+                "bounce": c.bounce_rays,
+                "shadow": c.shadow_rays,
+            },
+            "rays_per_second": self.rays_per_second(),
+            "average_path_length": self.average_path_length(),
+            "bvh_nodes": c.bvh_nodes,
+            "primitive_tests": c.primitive_tests,
+            "bvh_nodes_per_ray": self.nodes_per_ray(),
+            "primitive_tests_per_ray": self.primitive_tests_per_ray(),
+            "samples_per_pixel": self
+                .samples_per_pixel
+                .iter()
+                .map(|(samples, pixels)| json!({ "samples": samples, "pixels": pixels }))
+                .collect::<Vec<_>>(),
+            "peak_memory": self.peak_memory,
+            "thread_utilisation": self.thread_utilisation(),
+        })
+    }
+
+    /// Writes `to_json` to `file_path`.
+    pub fn save_json(&self, file_path: &str) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(&self.to_json()).expect("statistics are valid JSON");
+        std::fs::write(file_path, json + "\n")?;
+        Ok(())
+    }
+}
+
+fn ratio(a: u64, b: u64) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{take, Counters, RenderStats};
+    use std::time::Duration;
+
+    #[test]
+    fn should_count_per_thread_and_reset() {
+        take();
+        super::count_camera_ray();
+        super::count_bounce_ray();
+        super::count_bounce_ray();
+        std::thread::spawn(super::count_camera_ray).join().unwrap();
+
+        let counters = take();
+        assert_eq!(counters.camera_rays, 1);
+        assert_eq!(counters.bounce_rays, 2);
+        assert_eq!(take(), Counters::default());
+    }
+
+    #[test]
+    fn should_merge_renders() {
+        let render = |ms: u64, threads: usize| RenderStats {
+            wall_time: Duration::from_millis(ms),
+            counters: Counters {
+                camera_rays: 10,
+                bounce_rays: 20,
+                ..Default::default()
+            },
+            samples_per_pixel: [(4, 10)].into_iter().collect(),
+            thread_busy: vec![Duration::from_millis(ms); threads],
+            ..Default::default()
+        };
+        let mut stats = render(100, 1);
+        stats.merge(&render(100, 2));
+
+        assert_eq!(stats.wall_time, Duration::from_millis(200));
+        assert_eq!(stats.counters.rays(), 60);
+        assert_eq!(stats.average_path_length(), 3.0);
+        assert_eq!(stats.samples_per_pixel[&4], 20);
+        assert_eq!(stats.thread_utilisation(), vec![1.0, 0.5]);
+        assert_eq!(stats.to_json()["rays"]["camera"], 20);
+    }
+}
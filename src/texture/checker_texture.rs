@@ -21,9 +21,17 @@ impl Texture for CheckerTexture {
         let sines = f64::sin(10.0 * p.x) * f64::sin(10.0 * p.y) * f64::sin(10.0 * p.z);
 
         if sines < 0.0 {
-            return self.odd.value(u, v, p);
+            self.odd.value(u, v, p)
         } else {
-            return self.even.value(u, v, p);
+            self.even.value(u, v, p)
         }
     }
+
+    fn name(&self) -> &'static str {
+        "checker"
+    }
+
+    fn textures(&self) -> Vec<Arc<dyn Texture>> {
+        vec![self.odd.clone(), self.even.clone()]
+    }
 }
@@ -1,8 +1,19 @@
 pub mod checker_texture;
 pub mod solid_color;
 
+use std::sync::Arc;
+
 use crate::{utils::color::Color, vec3::Vec3};
 
+/// Colour varying over a surface, looked up by surface coordinates and hit point.
 pub trait Texture: Send + Sync {
     fn value(&self, u: f64, v: f64, p: &Vec3) -> Color;
+    /// Kind of texture, for diagnostics.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+    /// Textures this one is made of, for diagnostics.
+    fn textures(&self) -> Vec<Arc<dyn Texture>> {
+        Vec::new()
+    }
 }
@@ -11,7 +11,11 @@ impl SolidColor {
 }
 
 impl Texture for SolidColor {
-    fn value(&self, u: f64, v: f64, p: &Vec3) -> Color {
+    fn value(&self, _u: f64, _v: f64, _p: &Vec3) -> Color {
         self.color_value
     }
+
+    fn name(&self) -> &'static str {
+        "solid_color"
+    }
 }
@@ -1,11 +1,21 @@
 use std::{
+    cell::Cell,
     sync::{
-        mpsc::{self, Receiver, Sender},
+        mpsc::{self, Sender},
         Arc, Mutex,
     },
     thread,
 };
 
+thread_local! {
+    static WORKER: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Index of the pool worker running the calling thread, `None` outside of a pool.
+pub fn current_worker() -> Option<usize> {
+    WORKER.with(|w| w.get())
+}
+
 enum Message {
     NewJob(Job),
     Terminate,
@@ -24,29 +34,24 @@ impl<F: FnOnce()> FnBox for F {
 type Job = Box<dyn FnBox + Send + 'static>;
 
 struct Worker {
-    id: usize,
     thread: Option<thread::JoinHandle<()>>,
 }
 
 impl Worker {
     pub fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Message>>>) -> Self {
-        let thread = thread::spawn(move || loop {
-            let message = receiver.lock().unwrap().recv().unwrap();
-
-            match message {
-                Message::NewJob(job) => {
-                    println!("Worker {} got a job; executing.", id);
-                    job.call_box();
-                }
-                Message::Terminate => {
-                    println!("Worker {} was told to terminate.", id);
-                    break;
+        let thread = thread::spawn(move || {
+            WORKER.with(|w| w.set(Some(id)));
+            loop {
+                let message = receiver.lock().unwrap().recv().unwrap();
+
+                match message {
+                    Message::NewJob(job) => job.call_box(),
+                    Message::Terminate => break,
                 }
             }
         });
 
         Worker {
-            id,
             thread: Some(thread),
         }
     }
@@ -84,16 +89,11 @@ impl ThreadPool {
 
 impl Drop for ThreadPool {
     fn drop(&mut self) {
-        println!("Sending terminate message to all workers");
         for _ in &mut self.workers {
             self.sender.send(Message::Terminate).unwrap();
         }
 
-        println!("Shutting down all workers.");
-
         for worker in &mut self.workers {
-            println!("Shutting down worker {}", worker.id);
-
             // Replace Some(thread) with None
             if let Some(thread) = worker.thread.take() {
                 thread.join().unwrap(); // wait for thread to finish execution and obtain its result
@@ -0,0 +1,345 @@
+//! Vertex by vertex record of the paths traced for one pixel, to find out where a firefly
+//! or a NaN comes from.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use crate::{
+    hitable::Hitable,
+    integrator::path::PathIntegrator,
+    material::Lobe,
+    path::{Bounce, PathDepths},
+    ray::Ray,
+    sampler::Sampler,
+    utils::color::Color,
+    vec3::Vec3,
+};
+
+/// How a traced ray ended up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Hit a surface that sent it further.
+    Scatter,
+    /// Hit a surface that absorbed it, lights do.
+    Absorb,
+    /// Left the scene, it gets the background.
+    Escape,
+    /// Not traced, the path reached its maximum depth or that of the kind of its last
+    /// bounce. It gets black.
+    MaxDepth,
+    /// Not traced, the path was ended by Russian roulette. It gets black.
+    Roulette,
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Scatter => "scatter",
+            Event::Absorb => "absorb",
+            Event::Escape => "escape",
+            Event::MaxDepth => "max_depth",
+            Event::Roulette => "roulette",
+        }
+    }
+}
+
+/// Surface hit by a traced ray, the fields of its `HitRecord`.
+#[derive(Debug, Clone, Copy)]
+pub struct TracedHit {
+    pub p: Vec3,
+    pub normal: Vec3,
+    pub t: f64,
+    pub u: f64,
+    pub v: f64,
+    pub front_face: bool,
+    pub object_id: usize,
+    pub material: &'static str,
+    /// Address of the material, tells materials of the same kind apart within one run.
+    pub material_key: usize,
+}
+
+/// One ray of a traced path and what it met.
+#[derive(Debug, Clone)]
+pub struct TracedBounce {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub time: Option<f64>,
+    pub event: Event,
+    pub hit: Option<TracedHit>,
+    pub scatter_direction: Option<Vec3>,
+    pub attenuation: Option<Color>,
+    /// Kind of bounce the surface scattered the ray with.
+    pub lobe: Option<Lobe>,
+    /// Light emitted by the hit surface, or the background the ray ends on.
+    pub emitted: Color,
+    /// Product of the attenuations up to and including this vertex, over the chances of
+    /// surviving the roulette. Black once the path ended.
+    pub throughput: Color,
+    /// Light gathered by the path so far.
+    pub radiance: Color,
+}
+
+/// Path of one sample of a pixel.
+#[derive(Debug, Clone)]
+pub struct TracedSample {
+    pub sample: usize,
+    /// Position of the sample on the film, in pixels from the bottom left corner.
+    pub film: (f64, f64),
+    /// Empty when the camera has no ray for the sample, outside of a fisheye circle.
+    pub bounces: Vec<TracedBounce>,
+    /// Light the sample brings to the pixel, the same as the render up to rounding.
+    pub radiance: Color,
+}
+
+/// Follows `r` with the path integrator, drawing the same numbers from `sampler` as a
+/// render does, and records every vertex.
+pub fn trace_path(
+    r: Ray,
+    background: &Color,
+    world: &dyn Hitable,
+    depths: &PathDepths,
+    sampler: &mut dyn Sampler,
+) -> (Vec<TracedBounce>, Color) {
+    let mut bounces = Vec::new();
+    // The ray a depth limit or the roulette kept from being traced, and why
+    let mut ended = (depths.max == 0).then_some((Event::MaxDepth, r.origin, r.direction, r.time));
+
+    let radiance =
+        PathIntegrator::new(*depths).trace(&r, background, world, sampler, &mut |vertex| {
+            let event = match (vertex.hit, vertex.scattered) {
+                (None, _) => Event::Escape,
+                (Some(_), None) => Event::Absorb,
+                (Some(_), Some(_)) => Event::Scatter,
+            };
+            bounces.push(TracedBounce {
+                origin: vertex.ray.origin,
+                direction: vertex.ray.direction,
+                time: vertex.ray.time,
+                event,
+                hit: vertex.hit.map(|rec| TracedHit {
+                    p: rec.p,
+                    normal: rec.normal,
+                    t: rec.t,
+                    u: rec.u,
+                    v: rec.v,
+                    front_face: rec.front_face,
+                    object_id: rec.object_id,
+                    material: rec.mat_ptr.name(),
+                    material_key: Arc::as_ptr(&rec.mat_ptr) as *const () as usize,
+                }),
+                scatter_direction: vertex.scattered.map(|scattered| scattered.ray.direction),
+                attenuation: vertex.scattered.map(|scattered| scattered.attenuation),
+                lobe: vertex.scattered.map(|scattered| scattered.lobe),
+                emitted: vertex.emitted,
+                throughput: vertex.throughput,
+                radiance: vertex.radiance,
+            });
+
+            let event = match vertex.bounce {
+                Some(Bounce::MaxDepth) => Event::MaxDepth,
+                Some(Bounce::Roulette) => Event::Roulette,
+                _ => return,
+            };
+            if let Some(next) = vertex.scattered.map(|scattered| &scattered.ray) {
+                ended = Some((event, next.origin, next.direction, next.time));
+            }
+        });
+
+    if let Some((event, origin, direction, time)) = ended {
+        bounces.push(TracedBounce {
+            origin,
+            direction,
+            time,
+            event,
+            hit: None,
+            scatter_direction: None,
+            attenuation: None,
+            lobe: None,
+            emitted: Color::new(0.0, 0.0, 0.0),
+            throughput: Color::new(0.0, 0.0, 0.0),
+            radiance,
+        });
+    }
+    (bounces, radiance)
+}
+
+impl TracedSample {
+    /// One JSON object per bounce, then one for the whole sample. Values that are not
+    /// finite are written as `null`.
+    pub fn json_lines(&self) -> Vec<String> {
+        let mut lines = Vec::with_capacity(self.bounces.len() + 1);
+        for (index, bounce) in self.bounces.iter().enumerate() {
+            let mut line = json!({
+                "type": "bounce",
+                "sample": self.sample,
+                "bounce": index,
+                "origin": vector(bounce.origin),
+                "direction": vector(bounce.direction),
+                "time": bounce.time,
+                "event": bounce.event.name(),
+                "emitted": color(bounce.emitted),
+                "throughput": color(bounce.throughput),
+                "radiance": color(bounce.radiance),
+            });
+            if let Some(hit) = &bounce.hit {
+                line["hit"] = json!({
+                    "p": vector(hit.p),
+                    "normal": vector(hit.normal),
+                    "t": hit.t,
+                    "u": hit.u,
+                    "v": hit.v,
+                    "front_face": hit.front_face,
+                    "object_id": hit.object_id,
+                    "material": hit.material,
+                    "material_key": hit.material_key,
+                });
+            }
+            if let Some(direction) = bounce.scatter_direction {
+                line["scatter_direction"] = vector(direction);
+            }
+            if let Some(attenuation) = bounce.attenuation {
+                line["attenuation"] = color(attenuation);
+            }
+            if let Some(lobe) = bounce.lobe {
+                line["lobe"] = json!(lobe.name());
+            }
+            lines.push(line.to_string());
+        }
+
+        lines.push(
+            json!({
+                "type": "sample",
+                "sample": self.sample,
+                "film": [self.film.0, self.film.1],
+                "bounces": self.bounces.len(),
+                "radiance": color(self.radiance),
+            })
+            .to_string(),
+        );
+        lines
+    }
+}
+
+fn vector(v: Vec3) -> Value {
+    json!([v.x, v.y, v.z])
+}
+
+fn color(c: Color) -> Value {
+    json!([c.r, c.g, c.b])
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{Event, TracedBounce, TracedHit, TracedSample};
+    use crate::{material::Lobe, utils::color::Color, vec3::Vec3};
+
+    #[test]
+    fn should_write_one_line_per_bounce_and_sample() {
+        let hit = TracedBounce {
+            origin: Vec3::new(0.0, 1.5, 0.0),
+            direction: Vec3::new(0.0, 0.0, -1.0),
+            time: Some(0.25),
+            event: Event::Scatter,
+            hit: Some(TracedHit {
+                p: Vec3::new(0.0, 1.5, -2.0),
+                normal: Vec3::new(0.0, 0.0, 1.0),
+                t: 2.0,
+                u: 0.5,
+                v: 0.75,
+                front_face: true,
+                object_id: 4,
+                material: "crown \"glass\"",
+                material_key: 42,
+            }),
+            scatter_direction: Some(Vec3::new(0.0, 1.0, 0.0)),
+            attenuation: Some(Color::new(0.5, 0.5, 0.5)),
+            lobe: Some(Lobe::Diffuse),
+            emitted: Color::new(0.0, 0.0, 0.0),
+            throughput: Color::new(0.5, 0.5, 0.5),
+            radiance: Color::new(0.0, 0.0, 0.0),
+        };
+        let escape = TracedBounce {
+            origin: Vec3::new(0.0, 1.5, -2.0),
+            direction: Vec3::new(0.0, 1.0, 0.0),
+            time: Some(0.25),
+            event: Event::Escape,
+            hit: None,
+            scatter_direction: None,
+            attenuation: None,
+            lobe: None,
+            emitted: Color::new(0.5, 0.5, 0.5),
+            throughput: Color::new(0.5, 0.5, 0.5),
+            radiance: Color::new(f64::NAN, 0.25, f64::INFINITY),
+        };
+        let sample = TracedSample {
+            sample: 3,
+            film: (1.25, 2.5),
+            bounces: vec![hit, escape],
+            radiance: Color::new(0.25, 0.25, 0.25),
+        };
+
+        let lines = sample.json_lines();
+        assert_eq!(lines.len(), 3);
+        let values: Vec<Value> = lines
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            values[0],
+            json!({
+                "type": "bounce",
+                "sample": 3,
+                "bounce": 0,
+                "origin": [0.0, 1.5, 0.0],
+                "direction": [0.0, 0.0, -1.0],
+                "time": 0.25,
+                "event": "scatter",
+                "hit": {
+                    "p": [0.0, 1.5, -2.0],
+                    "normal": [0.0, 0.0, 1.0],
+                    "t": 2.0,
+                    "u": 0.5,
+                    "v": 0.75,
+                    "front_face": true,
+                    "object_id": 4,
+                    "material": "crown \"glass\"",
+                    "material_key": 42,
+                },
+                "scatter_direction": [0.0, 1.0, 0.0],
+                "attenuation": [0.5, 0.5, 0.5],
+                "lobe": "diffuse",
+                "emitted": [0.0, 0.0, 0.0],
+                "throughput": [0.5, 0.5, 0.5],
+                "radiance": [0.0, 0.0, 0.0],
+            })
+        );
+        assert_eq!(
+            values[1],
+            json!({
+                "type": "bounce",
+                "sample": 3,
+                "bounce": 1,
+                "origin": [0.0, 1.5, -2.0],
+                "direction": [0.0, 1.0, 0.0],
+                "time": 0.25,
+                "event": "escape",
+                "emitted": [0.5, 0.5, 0.5],
+                "throughput": [0.5, 0.5, 0.5],
+                "radiance": [null, 0.25, null],
+            })
+        );
+        assert_eq!(
+            values[2],
+            json!({
+                "type": "sample",
+                "sample": 3,
+                "film": [1.25, 2.5],
+                "bounces": 2,
+                "radiance": [0.25, 0.25, 0.25],
+            })
+        );
+    }
+}
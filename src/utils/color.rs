@@ -16,14 +16,6 @@ impl Color {
         Color { r, g, b }
     }
 
-    pub fn default() -> Self {
-        Color {
-            r: 0.0,
-            g: 0.0,
-            b: 0.0,
-        }
-    }
-
     pub fn random(range: Option<(f64, f64)>) -> Self {
         match range {
             None => Color::new(
@@ -38,6 +30,21 @@ impl Color {
             ),
         }
     }
+
+    /// Largest of the three channels.
+    pub fn max_component(&self) -> f64 {
+        self.r.max(self.g).max(self.b)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+        }
+    }
 }
 
 impl ops::Mul<f64> for Color {
@@ -71,7 +78,6 @@ impl From<Vec3> for Color {
 }
 
 #[cfg(test)]
-
 mod tests {
     use crate::utils::color::Color;
 
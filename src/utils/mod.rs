@@ -1,6 +1,7 @@
 pub mod color;
 
 use crate::{
+    error::{Error, Result},
     hitable::{hitable_list::HitableList, triangle::Triangle},
     material::Material,
     utils::color::Color,
@@ -11,7 +12,7 @@ use std::{cell::RefCell, path::Path, sync::Arc};
 use tobj::LoadOptions;
 
 // Static variables
-pub static INFINITY: f64 = std::f64::INFINITY;
+pub static INFINITY: f64 = f64::INFINITY;
 pub static PI: f64 = std::f64::consts::PI;
 
 pub fn write_color(c: Color, samples_per_pixel: f64) {
@@ -48,27 +49,38 @@ pub fn get_corrected_color(c: Color, samples_per_pixel: f64) -> Color {
 }
 
 pub fn random_int(start: usize, end: usize) -> usize {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(start..end)
+    RNG.with(|rng| rng.borrow_mut().gen_range(start..end))
 }
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
 
-pub fn obj_import_as_triangles(path: &str, material: Arc<dyn Material>) -> HitableList {
+pub fn obj_import_as_triangles(path: &str, material: Arc<dyn Material>) -> Result<HitableList> {
     let mut world = HitableList::new();
 
     let obj_file = Path::new(path);
 
     let (models, _) =
-        tobj::load_obj(&obj_file, &LoadOptions::default()).expect("Failed to load file");
+        tobj::load_obj(obj_file, &LoadOptions::default()).map_err(|source| Error::ObjLoad {
+            path: path.to_string(),
+            source,
+        })?;
 
-    for (_, model) in models.iter().enumerate() {
+    for model in models.iter() {
         let mesh = &model.mesh;
+        // tobj names the models of files without object or group statements itself
+        let name: Arc<str> = if model.name.is_empty() || model.name == "unnamed_object" {
+            obj_file
+                .file_stem()
+                .map_or_else(|| path.into(), |stem| stem.to_string_lossy().into())
+        } else {
+            model.name.as_str().into()
+        };
 
         // Mesh's indices are organized as triplets, so we'll
         // take them three at a time
+        let mut triangles = Vec::with_capacity(mesh.indices.len() / 3);
         for triangle in mesh.indices.chunks(3) {
             if let [v1, v2, v3] = *triangle {
                 let v1 = mesh.positions[(v1 as usize) * 3..(v1 as usize) * 3 + 3].to_vec();
@@ -79,12 +91,37 @@ pub fn obj_import_as_triangles(path: &str, material: Arc<dyn Material>) -> Hitab
                 let v2 = Vec3::new(v2[0] as f64, v2[1] as f64, v2[2] as f64);
                 let v3 = Vec3::new(v3[0] as f64, v3[1] as f64, v3[2] as f64);
 
-                world.add(Triangle::new(v1, v2, v3, material.clone()));
+                triangles.push(Triangle::new(v1, v2, v3, material.clone()));
             }
         }
+        world.add_mesh(name, triangles);
     }
 
-    world
+    Ok(world)
+}
+
+/// Whether `path` names an HDR format (EXR, Radiance HDR) keeping linear values.
+pub fn is_hdr(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| e == "exr" || e == "hdr")
+}
+
+/// Appends `_suffix` to the file name of `path`, "out.png" becomes "out_suffix.png".
+pub fn suffixed_path(path: &str, suffix: &str) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, suffix),
+    };
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
 }
 
 thread_local! {
@@ -92,6 +129,10 @@ thread_local! {
 }
 
 // Random Number Utilities
+pub fn seed_random(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
 pub fn random_double(range: Option<(f64, f64)>) -> f64 {
     RNG.with(|rng| match range {
         Some(range) => rng.borrow_mut().gen_range(range.0..range.1),
@@ -111,7 +152,10 @@ pub fn clamp(x: f64, min: f64, max: f64) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{random_double, random_int};
+    use std::sync::Arc;
+
+    use super::{obj_import_as_triangles, random_double, random_int, seed_random, suffixed_path};
+    use crate::{error::Error, material::lambertian::Lambertian, utils::color::Color};
 
     #[test]
     fn should_return_correct_default_random_numnber() {
@@ -121,7 +165,38 @@ mod tests {
 
     #[test]
     fn should_choose_only_3_axis() {
-        let r = random_int(0, 3);
-        assert!(r <= 2 && r >= 0);
+        let mut seen = [false; 3];
+        for _ in 0..1000 {
+            let r = random_int(0, 3);
+            assert!(r <= 2);
+            seen[r] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+
+    #[test]
+    fn should_report_missing_obj_file() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let result = obj_import_as_triangles("models/does_not_exist.obj", material);
+        assert!(matches!(result, Err(Error::ObjLoad { .. })));
+    }
+
+    #[test]
+    fn should_repeat_random_numbers_after_seeding() {
+        seed_random(7);
+        let first = (random_double(None), random_int(0, 100));
+        seed_random(7);
+        let second = (random_double(None), random_int(0, 100));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn should_suffix_file_names() {
+        assert_eq!(suffixed_path("out.png", "left"), "out_left.png");
+        assert_eq!(
+            suffixed_path("renders/cube.exr", "up"),
+            "renders/cube_up.exr"
+        );
+        assert_eq!(suffixed_path("out", "0001"), "out_0001");
     }
 }
@@ -1,8 +1,9 @@
 use std::ops;
 
-use crate::utils::random_double;
+use crate::utils::{random_double, PI};
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+/// Three dimensional vector used for points and directions.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -124,6 +125,36 @@ impl Vec3 {
             return p;
         }
     }
+
+    // Maps a point of the unit square to the unit disk with Shirley's concentric mapping
+    pub fn sample_in_unit_disk(u: (f64, f64)) -> Vec3 {
+        let offset = (2.0 * u.0 - 1.0, 2.0 * u.1 - 1.0);
+
+        if offset.0 == 0.0 && offset.1 == 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let (r, theta) = if offset.0.abs() > offset.1.abs() {
+            (offset.0, PI / 4.0 * (offset.1 / offset.0))
+        } else {
+            (offset.1, PI / 2.0 - PI / 4.0 * (offset.0 / offset.1))
+        };
+
+        Vec3::new(r * f64::cos(theta), r * f64::sin(theta), 0.0)
+    }
+
+    // Maps a point of the unit square to a uniformly distributed unit vector
+    pub fn sample_unit_vector(u: (f64, f64)) -> Vec3 {
+        let z = 1.0 - 2.0 * u.0;
+        let r = f64::sqrt(f64::max(0.0, 1.0 - z * z));
+        let phi = 2.0 * PI * u.1;
+        Vec3::new(r * f64::cos(phi), r * f64::sin(phi), z)
+    }
+
+    // Maps a point of the unit cube to a uniformly distributed point in the unit sphere
+    pub fn sample_in_unit_sphere(u: (f64, f64), r: f64) -> Vec3 {
+        Vec3::sample_unit_vector(u) * f64::cbrt(r)
+    }
 }
 
 impl ops::Add<Vec3> for Vec3 {
@@ -334,9 +365,9 @@ mod tests {
     #[test]
     fn should_be_indexable() {
         let v = Vec3::new(1.0, 2.0, 3.0);
-        let values = vec![1.0, 2.0, 3.0];
-        for i in 0..3 {
-            assert_eq!(Some(values[i]), v.at(i));
+        let values = [1.0, 2.0, 3.0];
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(Some(*value), v.at(i));
         }
     }
 
@@ -345,4 +376,18 @@ mod tests {
         let v = Vec3::new(1.0, 2.0, 3.0);
         assert_eq!(None, v.at(3));
     }
+
+    #[test]
+    fn should_sample_inside_unit_disk() {
+        for u in [(0.0, 0.0), (0.5, 0.5), (0.99, 0.1), (0.25, 0.8)] {
+            assert!(Vec3::sample_in_unit_disk(u).length() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn should_sample_unit_vector() {
+        for u in [(0.0, 0.0), (0.5, 0.5), (0.99, 0.1), (0.25, 0.8)] {
+            assert!((Vec3::sample_unit_vector(u).length() - 1.0).abs() < 1e-9);
+        }
+    }
 }
@@ -0,0 +1,251 @@
+//! Golden image regression tests.
+//!
+//! Renders small versions of the built-in scenes with a fixed seed and compares them
+//! against the reference images in `tests/golden`. A failing comparison writes the
+//! candidate render and an amplified difference image to the cargo test temporary
+//! directory. Run with `RTXON_BLESS=1` to (re)generate the references after an
+//! intentional change to the renderer.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use image::{Rgb, RgbImage};
+
+const SEED: u64 = 1;
+
+// Largest difference of a channel, out of 255, for a pixel to still count as matching
+const PIXEL_TOLERANCE: u8 = 16;
+// Fraction of pixels allowed to be outside the pixel tolerance
+const MAX_BAD_PIXEL_RATIO: f64 = 0.005;
+const MIN_PSNR: f64 = 35.0;
+
+struct GoldenScene {
+    name: &'static str,
+    scene: usize,
+    width: usize,
+    samples: usize,
+    max_depth: usize,
+    integrator: &'static str,
+}
+
+struct Comparison {
+    rmse: f64,
+    psnr: f64,
+    bad_pixel_ratio: f64,
+}
+
+impl Comparison {
+    fn passed(&self) -> bool {
+        self.psnr >= MIN_PSNR && self.bad_pixel_ratio <= MAX_BAD_PIXEL_RATIO
+    }
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn output_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn render(golden: &GoldenScene, extra_args: &[&str]) -> RgbImage {
+    std::fs::create_dir_all(output_dir()).unwrap();
+    let output_path = output_dir().join(format!("{}.candidate.png", golden.name));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rtxon"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["--scene", &golden.scene.to_string()])
+        .args(["--width", &golden.width.to_string()])
+        .args(["--samples", &golden.samples.to_string()])
+        .args(["--max-depth", &golden.max_depth.to_string()])
+        .args(["--integrator", golden.integrator])
+        .args(["--seed", &SEED.to_string()])
+        .args(["--threads", "4", "--chunks", "8"])
+        .args(["--output-path", output_path.to_str().unwrap()])
+        .args(extra_args)
+        .output()
+        .expect("Failed to run rtxon");
+
+    assert!(
+        output.status.success(),
+        "rtxon failed to render {}: {}",
+        golden.name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    image::open(&output_path).unwrap().to_rgb8()
+}
+
+fn compare(reference: &RgbImage, candidate: &RgbImage) -> Comparison {
+    let mut squared_error = 0.0;
+    let mut bad_pixels = 0;
+
+    for (a, b) in reference.pixels().zip(candidate.pixels()) {
+        let mut bad = false;
+        for c in 0..3 {
+            let diff = (a[c] as f64 - b[c] as f64) / 255.0;
+            squared_error += diff * diff;
+            bad |= a[c].abs_diff(b[c]) > PIXEL_TOLERANCE;
+        }
+        if bad {
+            bad_pixels += 1;
+        }
+    }
+
+    let pixels = (reference.width() * reference.height()) as f64;
+    let rmse = (squared_error / (3.0 * pixels)).sqrt();
+    let psnr = if rmse == 0.0 {
+        f64::INFINITY
+    } else {
+        20.0 * (1.0 / rmse).log10()
+    };
+
+    Comparison {
+        rmse,
+        psnr,
+        bad_pixel_ratio: bad_pixels as f64 / pixels,
+    }
+}
+
+fn difference_image(reference: &RgbImage, candidate: &RgbImage) -> RgbImage {
+    RgbImage::from_fn(reference.width(), reference.height(), |x, y| {
+        let (a, b) = (reference.get_pixel(x, y), candidate.get_pixel(x, y));
+        Rgb([0, 1, 2].map(|c| a[c].abs_diff(b[c]).saturating_mul(8)))
+    })
+}
+
+fn check(golden: GoldenScene) {
+    let candidate = render(&golden, &[]);
+    let reference_path = golden_dir().join(format!("{}.png", golden.name));
+
+    if env::var_os("RTXON_BLESS").is_some() {
+        candidate.save(&reference_path).unwrap();
+        return;
+    }
+
+    let reference = image::open(&reference_path)
+        .unwrap_or_else(|e| {
+            panic!(
+                "Missing reference {}, run with RTXON_BLESS=1 to create it: {}",
+                reference_path.display(),
+                e
+            )
+        })
+        .to_rgb8();
+
+    assert_eq!(
+        reference.dimensions(),
+        candidate.dimensions(),
+        "{} was rendered at a different resolution than its reference",
+        golden.name
+    );
+
+    let comparison = compare(&reference, &candidate);
+    if !comparison.passed() {
+        let diff_path = output_dir().join(format!("{}.diff.png", golden.name));
+        difference_image(&reference, &candidate)
+            .save(&diff_path)
+            .unwrap();
+
+        panic!(
+            "{} differs from its reference: RMSE {:.5}, PSNR {:.2} dB, {:.2}% pixels off by more than {}. Candidate and difference written to {}",
+            golden.name,
+            comparison.rmse,
+            comparison.psnr,
+            comparison.bad_pixel_ratio * 100.0,
+            PIXEL_TOLERANCE,
+            output_dir().display()
+        );
+    }
+}
+
+#[test]
+fn random_moving_scene() {
+    check(GoldenScene {
+        name: "random_moving_scene",
+        scene: 1,
+        width: 64,
+        samples: 8,
+        max_depth: 8,
+        integrator: "path",
+    });
+}
+
+#[test]
+fn checker_scene() {
+    check(GoldenScene {
+        name: "checker_scene",
+        scene: 2,
+        width: 64,
+        samples: 16,
+        max_depth: 8,
+        integrator: "path",
+    });
+}
+
+#[test]
+fn rectangular_light_scene() {
+    check(GoldenScene {
+        name: "rectangular_light_scene",
+        scene: 8,
+        width: 64,
+        samples: 16,
+        max_depth: 8,
+        integrator: "path",
+    });
+}
+
+#[test]
+fn cornell_box_scene() {
+    check(GoldenScene {
+        name: "cornell_box_scene",
+        scene: 9,
+        width: 48,
+        samples: 16,
+        max_depth: 8,
+        integrator: "path",
+    });
+}
+
+#[test]
+fn teapot_scene() {
+    check(GoldenScene {
+        name: "teapot_scene",
+        scene: 10,
+        width: 64,
+        samples: 64,
+        max_depth: 4,
+        // Its small light leaves the path traced teapot nearly black at these settings,
+        // ambient occlusion shows the whole mesh
+        integrator: "ao",
+    });
+}
+
+#[test]
+fn should_move_the_shutter_of_stills() {
+    let still = |name| GoldenScene {
+        name,
+        scene: 11,
+        width: 32,
+        samples: 4,
+        max_depth: 4,
+        integrator: "path",
+    };
+    let default = render(&still("animated_default_shutter"), &[]);
+    // The second half of the first second, the keyframed camera moves a lot meanwhile
+    let moved = render(
+        &still("animated_moved_shutter"),
+        &[
+            "--fps",
+            "1",
+            "--shutter-open",
+            "0.5",
+            "--shutter-close",
+            "1",
+        ],
+    );
+    assert_ne!(default, moved);
+}